@@ -1,5 +1,7 @@
 use std::os::raw::{c_char, c_int, c_void};
 
+use ior_core::handle::DirFiller;
+
 /// Opaque BenchFS context handle.
 pub enum BenchfsContext {}
 
@@ -75,6 +77,13 @@ unsafe extern "C" {
         newpath: *const c_char,
     ) -> c_int;
 
+    pub fn benchfs_readdir(
+        ctx: *mut BenchfsContext,
+        path: *const c_char,
+        buf: *mut c_void,
+        filler: DirFiller,
+    ) -> c_int;
+
     pub fn benchfs_access(
         ctx: *mut BenchfsContext,
         path: *const c_char,
@@ -7,7 +7,7 @@ use std::ptr;
 use ior_core::backend_options::BackendOptions;
 use ior_core::error::IorError;
 use ior_core::handle::{
-    FileHandle, OpenFlags, StatResult, XferCallback, XferDir, XferToken,
+    DirHandle, FileHandle, OpenFlags, StatResult, XferCallback, XferDir, XferToken,
 };
 use ior_core::Aiori;
 
@@ -380,4 +380,24 @@ impl Aiori for BenchfsBackend {
         }
         Ok(())
     }
+
+    /// List the whole directory up front; `readdir` hands out the names.
+    /// A failed listing reports the errno BenchFS set, or EIO if it set none.
+    fn opendir(&self, path: &str) -> Result<DirHandle, IorError> {
+        let ctx = self.ensure_init()?;
+        let cpath = Self::path_to_cstring(path)?;
+        let errno = || unsafe { libc::__errno_location() };
+        unsafe { *errno() = 0 };
+        DirHandle::from_filler(|buf, filler| unsafe {
+            benchfs_readdir(ctx, cpath.as_ptr(), buf, filler)
+        })
+        .map_err(|_| match unsafe { *errno() } {
+            0 => IorError::Io(libc::EIO),
+            err => IorError::Io(err),
+        })
+    }
+
+    fn readdir(&self, dir: &mut DirHandle) -> Result<Option<String>, IorError> {
+        dir.next_name()
+    }
 }
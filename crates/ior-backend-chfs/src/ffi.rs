@@ -1,4 +1,6 @@
-use std::os::raw::{c_char, c_int};
+use std::os::raw::{c_char, c_int, c_void};
+
+use ior_core::handle::DirFiller;
 
 unsafe extern "C" {
     pub fn chfs_init(server: *const c_char) -> c_int;
@@ -30,6 +32,7 @@ unsafe extern "C" {
     pub fn chfs_mkdir(path: *const c_char, mode: libc::mode_t) -> c_int;
     pub fn chfs_rmdir(path: *const c_char) -> c_int;
     pub fn chfs_access(path: *const c_char, mode: c_int) -> c_int;
+    pub fn chfs_readdir(path: *const c_char, buf: *mut c_void, filler: DirFiller) -> c_int;
 
     pub fn chfs_set_chunk_size(chunk_size: c_int);
     pub fn chfs_set_buf_size(buf_size: c_int);
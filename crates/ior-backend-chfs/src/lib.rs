@@ -7,7 +7,7 @@ use std::ptr;
 use ior_core::backend_options::BackendOptions;
 use ior_core::error::IorError;
 use ior_core::handle::{
-    FileHandle, OpenFlags, StatResult, XferCallback, XferDir, XferToken,
+    DirHandle, FileHandle, OpenFlags, StatResult, XferCallback, XferDir, XferToken,
 };
 use ior_core::Aiori;
 
@@ -176,13 +176,20 @@ impl Aiori for ChfsBackend {
     }
 
     fn delete(&self, path: &str) -> Result<(), IorError> {
+        match self.delete_strict(path) {
+            Err(IorError::NotFound) => Ok(()),
+            result => result,
+        }
+    }
+
+    fn delete_strict(&self, path: &str) -> Result<(), IorError> {
         let cpath = Self::path_to_cstring(path)?;
         let rc = unsafe { chfs_unlink(cpath.as_ptr()) };
         if rc < 0 {
-            let errno = Self::errno();
-            if errno != libc::ENOENT {
-                return Err(IorError::Io(errno));
-            }
+            return Err(match Self::errno() {
+                libc::ENOENT => IorError::NotFound,
+                errno => IorError::Io(errno),
+            });
         }
         Ok(())
     }
@@ -335,4 +342,15 @@ impl Aiori for ChfsBackend {
             })
         }
     }
+
+    /// List the whole directory up front; `readdir` hands out the names.
+    fn opendir(&self, path: &str) -> Result<DirHandle, IorError> {
+        let cpath = Self::path_to_cstring(path)?;
+        DirHandle::from_filler(|buf, filler| unsafe { chfs_readdir(cpath.as_ptr(), buf, filler) })
+            .map_err(|_| IorError::Io(Self::errno()))
+    }
+
+    fn readdir(&self, dir: &mut DirHandle) -> Result<Option<String>, IorError> {
+        dir.next_name()
+    }
 }
@@ -0,0 +1,757 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use ior_core::aiori::next_xfer_token;
+use ior_core::backend_options::BackendOptions;
+use ior_core::error::IorError;
+use ior_core::handle::{
+    DirHandle, FileHandle, OpenFlags, StatResult, XferCallback, XferDir, XferResult, XferToken,
+};
+use ior_core::Aiori;
+
+/// Files are stored in chunks of this size; chunks never written read as zeros.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+const S_IFDIR: u32 = 0o040000;
+const S_IFREG: u32 = 0o100000;
+
+/// Sparse contents of a file.
+#[derive(Default)]
+struct FileData {
+    chunks: HashMap<u64, Box<[u8]>>,
+    size: u64,
+}
+
+impl FileData {
+    /// Write `buf` at `offset`, growing the file. With `discard` only the
+    /// size changes.
+    fn write(&mut self, buf: &[u8], offset: u64, discard: bool) {
+        if !discard {
+            let mut done = 0;
+            while done < buf.len() {
+                let pos = offset + done as u64;
+                let start = (pos % CHUNK_SIZE as u64) as usize;
+                let n = (CHUNK_SIZE - start).min(buf.len() - done);
+                let chunk = self
+                    .chunks
+                    .entry(pos / CHUNK_SIZE as u64)
+                    .or_insert_with(|| vec![0u8; CHUNK_SIZE].into_boxed_slice());
+                chunk[start..start + n].copy_from_slice(&buf[done..done + n]);
+                done += n;
+            }
+        }
+        self.size = self.size.max(offset + buf.len() as u64);
+    }
+
+    /// Read into `buf` from `offset`, stopping at end of file. Returns the
+    /// number of bytes read.
+    fn read(&self, buf: &mut [u8], offset: u64) -> usize {
+        let len = self.size.saturating_sub(offset).min(buf.len() as u64) as usize;
+        let mut done = 0;
+        while done < len {
+            let pos = offset + done as u64;
+            let start = (pos % CHUNK_SIZE as u64) as usize;
+            let n = (CHUNK_SIZE - start).min(len - done);
+            match self.chunks.get(&(pos / CHUNK_SIZE as u64)) {
+                Some(chunk) => buf[done..done + n].copy_from_slice(&chunk[start..start + n]),
+                None => buf[done..done + n].fill(0),
+            }
+            done += n;
+        }
+        len
+    }
+
+    fn truncate(&mut self) {
+        self.chunks.clear();
+        self.size = 0;
+    }
+}
+
+struct MemFile {
+    data: RwLock<FileData>,
+    ctime: i64,
+}
+
+enum Node {
+    Dir { ctime: i64 },
+    File(Arc<MemFile>),
+}
+
+/// Files and directories of an in-memory file system, keyed by normalized
+/// path.
+///
+/// A path whose ancestors were never created is treated as lying under the
+/// root, so `/scratch/out` can be created without creating `/scratch`
+/// first. Below a created directory, parents must exist as in POSIX.
+#[derive(Default)]
+pub struct Namespace {
+    nodes: Mutex<BTreeMap<String, Node>>,
+}
+
+impl Namespace {
+    /// The namespace shared by every `MemoryBackend::new()` in the process,
+    /// so that ranks running as threads see each other's files.
+    pub fn global() -> Arc<Self> {
+        static GLOBAL: OnceLock<Arc<Namespace>> = OnceLock::new();
+        Arc::clone(GLOBAL.get_or_init(Default::default))
+    }
+
+    /// Paths of all files and directories, in sorted order.
+    pub fn paths(&self) -> Vec<String> {
+        self.nodes.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+/// Strip repeated and trailing slashes and `.` components.
+fn normalize(path: &str) -> String {
+    let parts: Vec<&str> = path
+        .split('/')
+        .filter(|p| !p.is_empty() && *p != ".")
+        .collect();
+    if path.starts_with('/') {
+        format!("/{}", parts.join("/"))
+    } else {
+        parts.join("/")
+    }
+}
+
+fn parent(path: &str) -> Option<&str> {
+    match path.rsplit_once('/') {
+        Some(("", name)) if !name.is_empty() => Some("/"),
+        Some((dir, _)) if !dir.is_empty() => Some(dir),
+        _ => None,
+    }
+}
+
+/// Check that `path` can be created: its closest created ancestor must be a
+/// directory and its parent.
+fn check_parent(nodes: &BTreeMap<String, Node>, path: &str) -> Result<(), IorError> {
+    let mut ancestor = parent(path);
+    let mut immediate = true;
+    while let Some(dir) = ancestor {
+        match nodes.get(dir) {
+            Some(Node::Dir { .. }) if immediate => return Ok(()),
+            Some(Node::Dir { .. }) => return Err(IorError::NotFound),
+            Some(Node::File(_)) => return Err(IorError::Io(libc::ENOTDIR)),
+            None => {}
+        }
+        immediate = false;
+        ancestor = parent(dir);
+    }
+    Ok(())
+}
+
+/// Prefix shared by every path below directory `dir`.
+fn child_prefix(dir: &str) -> String {
+    if dir.ends_with('/') {
+        dir.to_string()
+    } else {
+        format!("{}/", dir)
+    }
+}
+
+fn has_children(nodes: &BTreeMap<String, Node>, dir: &str) -> bool {
+    let prefix = child_prefix(dir);
+    nodes
+        .range(prefix.clone()..)
+        .next()
+        .is_some_and(|(path, _)| path.starts_with(&prefix))
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Open file: the file and the directions the open flags allow.
+struct MemHandle {
+    file: Arc<MemFile>,
+    readable: bool,
+    writable: bool,
+}
+
+/// Simulated service times. Async transfers overlap fully, so each one
+/// completes `xfer` after its submission regardless of queue depth.
+#[derive(Debug, Clone, Copy, Default)]
+struct LatencyModel {
+    /// Fixed time per transfer
+    per_xfer: Duration,
+    /// Transfer bandwidth in bytes/sec (0 = unlimited)
+    bandwidth: u64,
+    /// Time per metadata operation (open, stat, mkdir, ...)
+    metadata: Duration,
+}
+
+impl LatencyModel {
+    fn xfer(&self, len: i64) -> Duration {
+        let streaming = if self.bandwidth > 0 {
+            Duration::from_secs_f64(len as f64 / self.bandwidth as f64)
+        } else {
+            Duration::ZERO
+        };
+        self.per_xfer + streaming
+    }
+}
+
+fn wait(time: Duration) {
+    if !time.is_zero() {
+        std::thread::sleep(time);
+    }
+}
+
+/// An async transfer waiting for its simulated completion time.
+struct PendingXfer {
+    token: XferToken,
+    file: Arc<MemFile>,
+    dir: XferDir,
+    buf: *mut u8,
+    len: i64,
+    offset: i64,
+    user_data: usize,
+    callback: XferCallback,
+    ready: Instant,
+}
+
+// Safety: buf pointer is guaranteed valid by the caller until callback fires.
+unsafe impl Send for PendingXfer {}
+
+/// In-memory backend implementing the full Aiori trait.
+///
+/// File contents are kept sparsely in memory and transfers complete after a
+/// configurable simulated latency (zero by default), which makes it a
+/// deterministic backend for tests and a ceiling for harness overhead.
+pub struct MemoryBackend {
+    namespace: Arc<Namespace>,
+    latency: LatencyModel,
+    /// Drop written data and read zeros; only file sizes are kept
+    discard: bool,
+    pending: Mutex<Vec<PendingXfer>>,
+}
+
+impl MemoryBackend {
+    /// Create a backend on the process-wide namespace.
+    pub fn new() -> Self {
+        Self::with_namespace(Namespace::global())
+    }
+
+    /// Create a backend on its own namespace, shared only with backends
+    /// given the same `Arc`.
+    pub fn with_namespace(namespace: Arc<Namespace>) -> Self {
+        Self {
+            namespace,
+            latency: LatencyModel::default(),
+            discard: false,
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn nodes(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, Node>> {
+        wait(self.latency.metadata);
+        self.namespace.nodes.lock().unwrap()
+    }
+
+    fn handle(handle: &FileHandle) -> Result<&MemHandle, IorError> {
+        handle
+            .downcast_ref::<MemHandle>()
+            .ok_or(IorError::InvalidArgument)
+    }
+
+    /// Copy between `buf` and the file. Reads stop at end of file.
+    fn transfer(&self, file: &MemFile, dir: XferDir, buf: *mut u8, len: i64, offset: i64) -> i64 {
+        if len <= 0 {
+            return 0;
+        }
+        match dir {
+            XferDir::Write => {
+                let src = unsafe { std::slice::from_raw_parts(buf, len as usize) };
+                file.data.write().unwrap().write(src, offset as u64, self.discard);
+                len
+            }
+            XferDir::Read => {
+                let dst = unsafe { std::slice::from_raw_parts_mut(buf, len as usize) };
+                file.data.read().unwrap().read(dst, offset as u64) as i64
+            }
+        }
+    }
+
+    fn check_access(handle: &MemHandle, dir: XferDir) -> Result<(), IorError> {
+        let allowed = match dir {
+            XferDir::Read => handle.readable,
+            XferDir::Write => handle.writable,
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(IorError::Io(libc::EBADF))
+        }
+    }
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Aiori for MemoryBackend {
+    fn name(&self) -> &str {
+        "MEMORY"
+    }
+
+    fn configure(&mut self, options: &BackendOptions) -> Result<(), IorError> {
+        for (key, value) in options.for_prefix("memory") {
+            match key {
+                "latency_us" => {
+                    self.latency.per_xfer = Duration::from_micros(value.as_i64()?.max(0) as u64)
+                }
+                "bandwidth_mib" => {
+                    self.latency.bandwidth = value.as_i64()?.max(0) as u64 * 1024 * 1024
+                }
+                "meta_latency_us" => {
+                    self.latency.metadata = Duration::from_micros(value.as_i64()?.max(0) as u64)
+                }
+                "discard" => self.discard = value.as_bool(),
+                unknown => {
+                    eprintln!("WARNING: unknown MEMORY option: memory.{}", unknown);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn create(&self, path: &str, flags: OpenFlags) -> Result<FileHandle, IorError> {
+        self.open(path, flags | OpenFlags::CREAT | OpenFlags::RDWR)
+    }
+
+    fn open(&self, path: &str, flags: OpenFlags) -> Result<FileHandle, IorError> {
+        let path = normalize(path);
+        let mut nodes = self.nodes();
+        let file = match nodes.get(&path) {
+            Some(Node::Dir { .. }) => return Err(IorError::Io(libc::EISDIR)),
+            Some(Node::File(_)) if flags.contains(OpenFlags::CREAT | OpenFlags::EXCL) => {
+                return Err(IorError::Io(libc::EEXIST));
+            }
+            Some(Node::File(file)) => Arc::clone(file),
+            None if flags.contains(OpenFlags::CREAT) => {
+                check_parent(&nodes, &path)?;
+                let file = Arc::new(MemFile {
+                    data: RwLock::default(),
+                    ctime: now_secs(),
+                });
+                nodes.insert(path, Node::File(Arc::clone(&file)));
+                file
+            }
+            None => return Err(IorError::NotFound),
+        };
+        drop(nodes);
+
+        if flags.contains(OpenFlags::TRUNC) {
+            file.data.write().unwrap().truncate();
+        }
+        let writable = flags.intersects(OpenFlags::WRONLY | OpenFlags::RDWR);
+        Ok(FileHandle::new(MemHandle {
+            file,
+            readable: !flags.contains(OpenFlags::WRONLY) || flags.contains(OpenFlags::RDWR),
+            writable,
+        }))
+    }
+
+    fn close(&self, handle: FileHandle) -> Result<(), IorError> {
+        Self::handle(&handle)?;
+        Ok(())
+    }
+
+    /// Delete a file; a missing file is not an error, as for POSIX.
+    fn delete(&self, path: &str) -> Result<(), IorError> {
+        match self.delete_strict(path) {
+            Err(IorError::NotFound) => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Delete a file that must exist.
+    fn delete_strict(&self, path: &str) -> Result<(), IorError> {
+        let path = normalize(path);
+        let mut nodes = self.nodes();
+        match nodes.get(&path) {
+            Some(Node::Dir { .. }) => Err(IorError::Io(libc::EISDIR)),
+            Some(Node::File(_)) => {
+                nodes.remove(&path);
+                Ok(())
+            }
+            None => Err(IorError::NotFound),
+        }
+    }
+
+    fn fsync(&self, handle: &FileHandle) -> Result<(), IorError> {
+        Self::handle(handle)?;
+        Ok(())
+    }
+
+    fn get_file_size(&self, path: &str) -> Result<i64, IorError> {
+        self.stat(path).map(|st| st.size)
+    }
+
+    fn access(&self, path: &str, mode: i32) -> Result<bool, IorError> {
+        let _ = mode;
+        Ok(self.nodes().contains_key(&normalize(path)))
+    }
+
+    fn xfer_submit(
+        &self,
+        handle: &FileHandle,
+        dir: XferDir,
+        buf: *mut u8,
+        len: i64,
+        offset: i64,
+        user_data: usize,
+        callback: XferCallback,
+    ) -> Result<XferToken, IorError> {
+        let handle = Self::handle(handle)?;
+        Self::check_access(handle, dir)?;
+
+        let token = next_xfer_token();
+        self.pending.lock().unwrap().push(PendingXfer {
+            token,
+            file: Arc::clone(&handle.file),
+            dir,
+            buf,
+            len,
+            offset,
+            user_data,
+            callback,
+            ready: Instant::now() + self.latency.xfer(len),
+        });
+        Ok(token)
+    }
+
+    /// Complete transfers whose simulated latency has passed, earliest
+    /// first. Data is copied at completion.
+    fn poll(&self, max_completions: usize) -> Result<usize, IorError> {
+        let now = Instant::now();
+        let mut ready = {
+            let mut pending = self.pending.lock().unwrap();
+            let (ready, waiting) = pending.drain(..).partition(|x: &PendingXfer| x.ready <= now);
+            *pending = waiting;
+            ready
+        };
+        ready.sort_by_key(|x| x.ready);
+        if ready.len() > max_completions {
+            // Put back what this call does not complete
+            self.pending
+                .lock()
+                .unwrap()
+                .extend(ready.drain(max_completions..));
+        }
+
+        let count = ready.len();
+        for xfer in ready {
+            let result = XferResult {
+                token: xfer.token,
+                bytes_transferred: self.transfer(
+                    &xfer.file,
+                    xfer.dir,
+                    xfer.buf,
+                    xfer.len,
+                    xfer.offset,
+                ),
+                error: 0,
+                user_data: xfer.user_data,
+            };
+            (xfer.callback)(&result);
+        }
+        Ok(count)
+    }
+
+    fn cancel(&self, token: XferToken) -> Result<(), IorError> {
+        let xfer = {
+            let mut pending = self.pending.lock().unwrap();
+            let pos = pending
+                .iter()
+                .position(|x| x.token == token)
+                .ok_or(IorError::NotFound)?;
+            pending.remove(pos)
+        };
+        let result = XferResult {
+            token,
+            bytes_transferred: 0,
+            error: libc::ECANCELED,
+            user_data: xfer.user_data,
+        };
+        (xfer.callback)(&result);
+        Ok(())
+    }
+
+    fn mkdir(&self, path: &str, mode: u32) -> Result<(), IorError> {
+        let _ = mode;
+        let path = normalize(path);
+        let mut nodes = self.nodes();
+        if nodes.contains_key(&path) {
+            return Err(IorError::Io(libc::EEXIST));
+        }
+        check_parent(&nodes, &path)?;
+        nodes.insert(path, Node::Dir { ctime: now_secs() });
+        Ok(())
+    }
+
+    fn rmdir(&self, path: &str) -> Result<(), IorError> {
+        let path = normalize(path);
+        let mut nodes = self.nodes();
+        match nodes.get(&path) {
+            Some(Node::Dir { .. }) if has_children(&nodes, &path) => {
+                Err(IorError::Io(libc::ENOTEMPTY))
+            }
+            Some(Node::Dir { .. }) => {
+                nodes.remove(&path);
+                Ok(())
+            }
+            Some(Node::File(_)) => Err(IorError::Io(libc::ENOTDIR)),
+            None => Err(IorError::NotFound),
+        }
+    }
+
+    fn stat(&self, path: &str) -> Result<StatResult, IorError> {
+        let nodes = self.nodes();
+        let (size, mode, ctime) = match nodes.get(&normalize(path)) {
+            Some(Node::Dir { ctime }) => (0, S_IFDIR | 0o755, *ctime),
+            Some(Node::File(file)) => (
+                file.data.read().unwrap().size as i64,
+                S_IFREG | 0o644,
+                file.ctime,
+            ),
+            None => return Err(IorError::NotFound),
+        };
+        Ok(StatResult {
+            size,
+            mode,
+            nlink: 1,
+            uid: 0,
+            gid: 0,
+            atime: ctime,
+            mtime: ctime,
+            ctime,
+        })
+    }
+
+    /// Rename a file or directory, moving everything below a directory.
+    fn rename(&self, old_path: &str, new_path: &str) -> Result<(), IorError> {
+        let old_path = normalize(old_path);
+        let new_path = normalize(new_path);
+        if old_path == new_path {
+            return Ok(());
+        }
+        let mut nodes = self.nodes();
+        let old_is_dir = match nodes.get(&old_path) {
+            Some(Node::Dir { .. }) => true,
+            Some(Node::File(_)) => false,
+            None => return Err(IorError::NotFound),
+        };
+        if new_path.starts_with(&child_prefix(&old_path)) {
+            return Err(IorError::InvalidArgument);
+        }
+        check_parent(&nodes, &new_path)?;
+        match nodes.get(&new_path) {
+            Some(Node::Dir { .. }) if !old_is_dir => return Err(IorError::Io(libc::EISDIR)),
+            Some(Node::File(_)) if old_is_dir => return Err(IorError::Io(libc::ENOTDIR)),
+            Some(Node::Dir { .. }) if has_children(&nodes, &new_path) => {
+                return Err(IorError::Io(libc::ENOTEMPTY));
+            }
+            _ => {}
+        }
+
+        let node = nodes.remove(&old_path).unwrap();
+        nodes.insert(new_path.clone(), node);
+        if old_is_dir {
+            let prefix = child_prefix(&old_path);
+            let moved: Vec<String> = nodes
+                .range(prefix.clone()..)
+                .take_while(|(path, _)| path.starts_with(&prefix))
+                .map(|(path, _)| path.clone())
+                .collect();
+            for path in moved {
+                let node = nodes.remove(&path).unwrap();
+                nodes.insert(format!("{}/{}", new_path, &path[prefix.len()..]), node);
+            }
+        }
+        Ok(())
+    }
+
+    fn mknod(&self, path: &str) -> Result<(), IorError> {
+        let handle = self.open(path, OpenFlags::CREAT | OpenFlags::EXCL | OpenFlags::RDONLY)?;
+        self.close(handle)
+    }
+
+    /// Snapshot the names directly below `path`.
+    fn opendir(&self, path: &str) -> Result<DirHandle, IorError> {
+        let path = normalize(path);
+        let nodes = self.nodes();
+        match nodes.get(&path) {
+            Some(Node::Dir { .. }) => {}
+            Some(Node::File(_)) => return Err(IorError::Io(libc::ENOTDIR)),
+            // A directory that was never created exists if something is below it
+            None if has_children(&nodes, &path) => {}
+            None => return Err(IorError::NotFound),
+        }
+        let prefix = child_prefix(&path);
+        let names: Vec<String> = nodes
+            .range(prefix.clone()..)
+            .take_while(|(child, _)| child.starts_with(&prefix))
+            .map(|(child, _)| &child[prefix.len()..])
+            .filter(|name| !name.contains('/'))
+            .map(String::from)
+            .collect();
+        Ok(DirHandle::new(names.into_iter()))
+    }
+
+    fn readdir(&self, dir: &mut DirHandle) -> Result<Option<String>, IorError> {
+        let names = dir
+            .downcast_mut::<std::vec::IntoIter<String>>()
+            .ok_or(IorError::InvalidArgument)?;
+        Ok(names.next())
+    }
+
+    /// Copy directly, after the simulated latency of the transfer.
+    fn xfer_sync(
+        &self,
+        handle: &FileHandle,
+        dir: XferDir,
+        buf: *mut u8,
+        len: i64,
+        offset: i64,
+    ) -> Result<i64, IorError> {
+        let handle = Self::handle(handle)?;
+        Self::check_access(handle, dir)?;
+        wait(self.latency.xfer(len));
+        Ok(self.transfer(&handle.file, dir, buf, len, offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backend() -> MemoryBackend {
+        MemoryBackend::with_namespace(Arc::default())
+    }
+
+    fn write(backend: &MemoryBackend, handle: &FileHandle, data: &[u8], offset: i64) -> i64 {
+        let buf = data.as_ptr() as *mut u8;
+        backend
+            .xfer_sync(handle, XferDir::Write, buf, data.len() as i64, offset)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sparse_write_read() {
+        let backend = backend();
+        let handle = backend.create("/f", OpenFlags::WRONLY).unwrap();
+        assert_eq!(write(&backend, &handle, b"head", 0), 4);
+        // Crosses a chunk boundary, leaving a hole before it
+        let tail = vec![7u8; 100];
+        let offset = 3 * CHUNK_SIZE as i64 - 50;
+        assert_eq!(write(&backend, &handle, &tail, offset), 100);
+        backend.close(handle).unwrap();
+        assert_eq!(backend.get_file_size("/f").unwrap(), offset + 100);
+
+        let handle = backend.open("/f", OpenFlags::RDONLY).unwrap();
+        let mut buf = vec![1u8; 200];
+        let read = backend
+            .xfer_sync(&handle, XferDir::Read, buf.as_mut_ptr(), 200, offset - 100)
+            .unwrap();
+        // The hole reads as zeros
+        assert_eq!(read, 200);
+        assert!(buf[..100].iter().all(|&b| b == 0));
+        assert!(buf[100..].iter().all(|&b| b == 7));
+        let read = backend
+            .xfer_sync(&handle, XferDir::Read, buf.as_mut_ptr(), 200, offset + 50)
+            .unwrap();
+        // Short read at end of file
+        assert_eq!(read, 50);
+        assert!(backend
+            .xfer_sync(&handle, XferDir::Write, buf.as_mut_ptr(), 1, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_namespace_rules() {
+        let backend = backend();
+        // Ancestors that were never created act as the root
+        backend.mkdir("/scratch/run", 0o755).unwrap();
+        backend.mkdir("/scratch/run/a", 0o755).unwrap();
+        assert!(matches!(
+            backend.mkdir("/scratch/run/b/c", 0o755),
+            Err(IorError::NotFound)
+        ));
+        backend.mknod("/scratch/run/a/f").unwrap();
+        assert!(backend.mknod("/scratch/run/a/f").is_err());
+        assert!(backend.mkdir("/scratch/run/a/f/g", 0o755).is_err());
+        assert!(matches!(
+            backend.rmdir("/scratch/run/a"),
+            Err(IorError::Io(libc::ENOTEMPTY))
+        ));
+
+        let mut dir = backend.opendir("/scratch/run").unwrap();
+        assert_eq!(backend.readdir(&mut dir).unwrap().as_deref(), Some("a"));
+        assert_eq!(backend.readdir(&mut dir).unwrap(), None);
+        backend.closedir(dir).unwrap();
+        assert!(backend.opendir("/scratch/run/a/f").is_err());
+
+        backend.rename("/scratch/run/a", "/scratch/run/b").unwrap();
+        assert!(!backend.access("/scratch/run/a/f", 0).unwrap());
+        let st = backend.stat("/scratch/run//b/./f").unwrap();
+        assert_eq!(st.mode & S_IFREG, S_IFREG);
+
+        backend.delete("/scratch/run/b/f").unwrap();
+        backend.rmdir("/scratch/run/b").unwrap();
+        backend.rmdir("/scratch/run").unwrap();
+        assert!(backend.namespace.paths().is_empty());
+    }
+
+    #[test]
+    fn test_async_completes_after_latency() {
+        let mut backend = backend();
+        let mut options = BackendOptions::new();
+        options.insert("memory.latency_us".into(), ior_core::OptionValue::Str("20000".into()));
+        backend.configure(&options).unwrap();
+
+        extern "C" fn record(result: *const XferResult) {
+            let res = unsafe { &*result };
+            let done = res.user_data as *mut Vec<i64>;
+            unsafe { (*done).push(res.bytes_transferred) };
+        }
+
+        let handle = backend.create("f", OpenFlags::RDWR).unwrap();
+        let data = vec![3u8; 4096];
+        let mut done: Vec<i64> = Vec::new();
+        let done_ptr = &mut done as *mut Vec<i64> as usize;
+        for i in 0..4 {
+            backend
+                .xfer_submit(
+                    &handle,
+                    XferDir::Write,
+                    data.as_ptr() as *mut u8,
+                    4096,
+                    i * 4096,
+                    done_ptr,
+                    record,
+                )
+                .unwrap();
+        }
+        let token = backend
+            .xfer_submit(&handle, XferDir::Write, data.as_ptr() as *mut u8, 1, 0, done_ptr, record)
+            .unwrap();
+        backend.cancel(token).unwrap();
+        assert_eq!(done, [0]);
+        // Nothing is visible before the latency has passed
+        assert_eq!(backend.poll(16).unwrap(), 0);
+        assert_eq!(backend.get_file_size("f").unwrap(), 0);
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(backend.poll(3).unwrap(), 3);
+        assert_eq!(backend.poll(16).unwrap(), 1);
+        assert_eq!(done, [0, 4096, 4096, 4096, 4096]);
+        assert_eq!(backend.get_file_size("f").unwrap(), 4 * 4096);
+    }
+}
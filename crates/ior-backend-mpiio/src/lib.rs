@@ -0,0 +1,329 @@
+use std::ffi::CString;
+use std::os::raw::{c_int, c_void};
+
+use mpi::ffi;
+use mpi::raw::AsRaw;
+use mpi::topology::SimpleCommunicator;
+
+use ior_backend_posix::PosixBackend;
+use ior_core::backend_options::BackendOptions;
+use ior_core::error::IorError;
+use ior_core::handle::{
+    FileHandle, OpenFlags, StatResult, XferCallback, XferDir, XferToken,
+};
+use ior_core::Aiori;
+
+/// Wrapper holding an MPI file handle.
+struct MpiFile {
+    fh: ffi::MPI_File,
+}
+
+// Safety: the handle is only used from the rank's main thread; MPI_File is an
+// opaque pointer in some MPI implementations, which makes it !Send by default.
+unsafe impl Send for MpiFile {}
+unsafe impl Sync for MpiFile {}
+
+/// MPI-IO backend implementing the Aiori trait.
+///
+/// Reference: `aiori-MPIIO.c`
+pub struct MpiioBackend {
+    /// Communicator passed to MPI_File_open (MPI_COMM_SELF for file-per-process).
+    comm: ffi::MPI_Comm,
+    /// Use the collective `_all` transfer variants.
+    collective: bool,
+    /// Files are opened per process, so `_all` calls involve one rank only.
+    file_per_proc: bool,
+    /// Hints passed to MPI_File_open (MPI_INFO_NULL if none).
+    info: ffi::MPI_Info,
+    /// Directory and stat operations go through POSIX, as in C IOR.
+    posix: PosixBackend,
+}
+
+impl MpiioBackend {
+    /// Create a backend opening files over `comm`. The communicator must
+    /// outlive the backend.
+    pub fn new(comm: &SimpleCommunicator, file_per_proc: bool) -> Self {
+        let comm = if file_per_proc {
+            unsafe { ffi::RSMPI_COMM_SELF }
+        } else {
+            comm.as_raw()
+        };
+        Self {
+            comm,
+            collective: false,
+            file_per_proc,
+            info: unsafe { ffi::RSMPI_INFO_NULL },
+            posix: PosixBackend::new(false),
+        }
+    }
+
+    /// Convert IOR OpenFlags to MPI_MODE_* access mode.
+    fn to_amode(flags: OpenFlags) -> c_int {
+        let mut amode: c_int = 0;
+
+        if flags.contains(OpenFlags::RDONLY)
+            && !flags.contains(OpenFlags::WRONLY | OpenFlags::RDWR)
+        {
+            amode |= ffi::MPI_MODE_RDONLY as c_int;
+        }
+        // MPI_MODE_WRONLY | MPI_MODE_RDWR is erroneous; RDWR wins
+        if flags.contains(OpenFlags::WRONLY) && !flags.contains(OpenFlags::RDWR) {
+            amode |= ffi::MPI_MODE_WRONLY as c_int;
+        }
+        if flags.contains(OpenFlags::RDWR) {
+            amode |= ffi::MPI_MODE_RDWR as c_int;
+        }
+        if flags.contains(OpenFlags::APPEND) {
+            amode |= ffi::MPI_MODE_APPEND as c_int;
+        }
+        if flags.contains(OpenFlags::CREAT) {
+            amode |= ffi::MPI_MODE_CREATE as c_int;
+        }
+        if flags.contains(OpenFlags::EXCL) {
+            amode |= ffi::MPI_MODE_EXCL as c_int;
+        }
+
+        amode
+    }
+
+    fn path_to_cstring(path: &str) -> Result<CString, IorError> {
+        CString::new(path).map_err(|_| IorError::InvalidArgument)
+    }
+
+    /// Map an MPI return code to IorError (MPI error classes carry no errno).
+    fn check(rc: c_int) -> Result<(), IorError> {
+        if rc != ffi::MPI_SUCCESS as c_int {
+            return Err(IorError::Io(libc::EIO));
+        }
+        Ok(())
+    }
+
+    fn open_with(
+        &self,
+        comm: ffi::MPI_Comm,
+        path: &str,
+        amode: c_int,
+    ) -> Result<ffi::MPI_File, IorError> {
+        let cpath = Self::path_to_cstring(path)?;
+        let mut fh = unsafe { ffi::RSMPI_FILE_NULL };
+        let rc = unsafe { ffi::MPI_File_open(comm, cpath.as_ptr(), amode, self.info, &mut fh) };
+        Self::check(rc)?;
+        Ok(fh)
+    }
+
+    /// Add one `key=value` hint, creating the MPI_Info object on first use.
+    fn set_hint(&mut self, hint: &str) -> Result<(), IorError> {
+        let (key, value) = hint.split_once('=').ok_or_else(|| {
+            eprintln!("ERROR: MPIIO hint must be key=value: {}", hint);
+            IorError::InvalidArgument
+        })?;
+        let ckey = Self::path_to_cstring(key.trim())?;
+        let cvalue = Self::path_to_cstring(value.trim())?;
+        unsafe {
+            if self.info == ffi::RSMPI_INFO_NULL {
+                Self::check(ffi::MPI_Info_create(&mut self.info))?;
+            }
+            Self::check(ffi::MPI_Info_set(self.info, ckey.as_ptr(), cvalue.as_ptr()))
+        }
+    }
+}
+
+impl Drop for MpiioBackend {
+    fn drop(&mut self) {
+        unsafe {
+            if self.info != ffi::RSMPI_INFO_NULL {
+                ffi::MPI_Info_free(&mut self.info);
+            }
+        }
+    }
+}
+
+impl Aiori for MpiioBackend {
+    fn name(&self) -> &str {
+        "MPIIO"
+    }
+
+    fn configure(&mut self, options: &BackendOptions) -> Result<(), IorError> {
+        for (key, value) in options.for_prefix("mpiio") {
+            match key {
+                "collective" => self.collective = value.as_bool(),
+                "hint" => {
+                    // Comma-separated list: --mpiio.hint=striping_factor=4,cb_nodes=2
+                    for hint in value.as_str().unwrap_or("").split(',') {
+                        if !hint.is_empty() {
+                            self.set_hint(hint)?;
+                        }
+                    }
+                }
+                unknown => {
+                    if let Some(hint_key) = unknown.strip_prefix("hint.") {
+                        // Per-key form: --mpiio.hint.striping_factor=4
+                        let hint = format!("{}={}", hint_key, value.as_str().unwrap_or(""));
+                        self.set_hint(&hint)?;
+                    } else {
+                        eprintln!("WARNING: unknown MPIIO option: mpiio.{}", unknown);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Create a new file (collective over the test communicator).
+    /// Reference: `aiori-MPIIO.c:MPIIO_Create`
+    fn create(&self, path: &str, flags: OpenFlags) -> Result<FileHandle, IorError> {
+        let amode = Self::to_amode(flags | OpenFlags::CREAT | OpenFlags::RDWR);
+        let fh = self.open_with(self.comm, path, amode)?;
+        Ok(FileHandle::new(MpiFile { fh }))
+    }
+
+    /// Open an existing file. Reference: `aiori-MPIIO.c:MPIIO_Open`
+    fn open(&self, path: &str, flags: OpenFlags) -> Result<FileHandle, IorError> {
+        let fh = self.open_with(self.comm, path, Self::to_amode(flags))?;
+        Ok(FileHandle::new(MpiFile { fh }))
+    }
+
+    /// Close a file. Reference: `aiori-MPIIO.c:MPIIO_Close`
+    fn close(&self, handle: FileHandle) -> Result<(), IorError> {
+        let mf = handle
+            .downcast_ref::<MpiFile>()
+            .ok_or(IorError::InvalidArgument)?;
+        let mut fh = mf.fh;
+        Self::check(unsafe { ffi::MPI_File_close(&mut fh) })
+    }
+
+    /// Delete a file. Reference: `aiori-MPIIO.c:MPIIO_Delete`
+    fn delete(&self, path: &str) -> Result<(), IorError> {
+        let cpath = Self::path_to_cstring(path)?;
+        Self::check(unsafe { ffi::MPI_File_delete(cpath.as_ptr(), ffi::RSMPI_INFO_NULL) })
+    }
+
+    /// Sync a file (collective). Reference: `aiori-MPIIO.c:MPIIO_Fsync`
+    fn fsync(&self, handle: &FileHandle) -> Result<(), IorError> {
+        let mf = handle
+            .downcast_ref::<MpiFile>()
+            .ok_or(IorError::InvalidArgument)?;
+        Self::check(unsafe { ffi::MPI_File_sync(mf.fh) })
+    }
+
+    /// Get file size by opening it on MPI_COMM_SELF.
+    /// Reference: `aiori-MPIIO.c:MPIIO_GetFileSize`
+    fn get_file_size(&self, path: &str) -> Result<i64, IorError> {
+        let self_comm = unsafe { ffi::RSMPI_COMM_SELF };
+        let mut fh = self.open_with(self_comm, path, ffi::MPI_MODE_RDONLY as c_int)?;
+        let mut size: ffi::MPI_Offset = 0;
+        let rc = unsafe { ffi::MPI_File_get_size(fh, &mut size) };
+        unsafe {
+            ffi::MPI_File_close(&mut fh);
+        }
+        Self::check(rc)?;
+        Ok(size as i64)
+    }
+
+    /// Check accessibility by trying to open the file on MPI_COMM_SELF.
+    /// Reference: `aiori-MPIIO.c:MPIIO_Access`
+    fn access(&self, path: &str, mode: i32) -> Result<bool, IorError> {
+        let amode = if mode & libc::W_OK != 0 {
+            ffi::MPI_MODE_RDWR
+        } else {
+            ffi::MPI_MODE_RDONLY
+        };
+        let self_comm = unsafe { ffi::RSMPI_COMM_SELF };
+        match self.open_with(self_comm, path, amode as c_int) {
+            Ok(mut fh) => {
+                unsafe {
+                    ffi::MPI_File_close(&mut fh);
+                }
+                Ok(true)
+            }
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Transfer with explicit offsets, independent or collective.
+    /// Reference: `aiori-MPIIO.c:MPIIO_Xfer`
+    fn xfer_sync(
+        &self,
+        handle: &FileHandle,
+        dir: XferDir,
+        buf: *mut u8,
+        len: i64,
+        offset: i64,
+    ) -> Result<i64, IorError> {
+        let mf = handle
+            .downcast_ref::<MpiFile>()
+            .ok_or(IorError::InvalidArgument)?;
+        // MPI counts are int
+        let count = c_int::try_from(len).map_err(|_| IorError::InvalidArgument)?;
+        let off = offset as ffi::MPI_Offset;
+
+        unsafe {
+            let datatype = ffi::RSMPI_UINT8_T;
+            let mut status: ffi::MPI_Status = std::mem::zeroed();
+            let rc = match (dir, self.collective) {
+                (XferDir::Write, false) => ffi::MPI_File_write_at(
+                    mf.fh, off, buf as *const c_void, count, datatype, &mut status,
+                ),
+                (XferDir::Write, true) => ffi::MPI_File_write_at_all(
+                    mf.fh, off, buf as *const c_void, count, datatype, &mut status,
+                ),
+                (XferDir::Read, false) => ffi::MPI_File_read_at(
+                    mf.fh, off, buf as *mut c_void, count, datatype, &mut status,
+                ),
+                (XferDir::Read, true) => ffi::MPI_File_read_at_all(
+                    mf.fh, off, buf as *mut c_void, count, datatype, &mut status,
+                ),
+            };
+            Self::check(rc)?;
+
+            let mut transferred: c_int = 0;
+            Self::check(ffi::MPI_Get_count(&status, datatype, &mut transferred))?;
+            // Only this rank would call `_all` again for the remainder
+            if self.collective && transferred < count {
+                let done = transferred as i64;
+                return Err(IorError::ShortTransfer { done, len });
+            }
+            Ok(transferred as i64)
+        }
+    }
+
+    fn collective_xfer(&self) -> bool {
+        self.collective && !self.file_per_proc
+    }
+
+    fn xfer_submit(
+        &self,
+        _handle: &FileHandle,
+        _dir: XferDir,
+        _buf: *mut u8,
+        _len: i64,
+        _offset: i64,
+        _user_data: usize,
+        _callback: XferCallback,
+    ) -> Result<XferToken, IorError> {
+        Err(IorError::NotSupported)
+    }
+
+    fn poll(&self, _max_completions: usize) -> Result<usize, IorError> {
+        Err(IorError::NotSupported)
+    }
+
+    fn cancel(&self, _token: XferToken) -> Result<(), IorError> {
+        Err(IorError::NotSupported)
+    }
+
+    /// Reference: `aiori-MPIIO.c` (uses `aiori_posix_mkdir`)
+    fn mkdir(&self, path: &str, mode: u32) -> Result<(), IorError> {
+        self.posix.mkdir(path, mode)
+    }
+
+    /// Reference: `aiori-MPIIO.c` (uses `aiori_posix_rmdir`)
+    fn rmdir(&self, path: &str) -> Result<(), IorError> {
+        self.posix.rmdir(path)
+    }
+
+    /// Reference: `aiori-MPIIO.c` (uses `aiori_posix_stat`)
+    fn stat(&self, path: &str) -> Result<StatResult, IorError> {
+        self.posix.stat(path)
+    }
+}
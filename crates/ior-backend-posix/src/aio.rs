@@ -0,0 +1,225 @@
+//! Linux native AIO engine (`io_setup`/`io_submit`/`io_getevents`).
+//!
+//! Calls the raw syscalls rather than linking libaio. Submissions are only
+//! truly asynchronous for files opened with O_DIRECT; buffered I/O completes
+//! inside `io_submit`. Short transfers are resubmitted for the remainder.
+
+use std::collections::HashMap;
+use std::os::raw::c_long;
+use std::sync::Mutex;
+
+use ior_core::error::IorError;
+use ior_core::handle::{XferDir, XferToken};
+
+use crate::{InflightOp, PendingOp};
+
+const IOCB_CMD_PREAD: u16 = 0;
+const IOCB_CMD_PWRITE: u16 = 1;
+
+/// `struct iocb` from `<linux/aio_abi.h>` (little-endian layout).
+#[repr(C)]
+#[derive(Default)]
+struct Iocb {
+    aio_data: u64,
+    aio_key: u32,
+    aio_rw_flags: i32,
+    aio_lio_opcode: u16,
+    aio_reqprio: i16,
+    aio_fildes: u32,
+    aio_buf: u64,
+    aio_nbytes: u64,
+    aio_offset: i64,
+    aio_reserved2: u64,
+    aio_flags: u32,
+    aio_resfd: u32,
+}
+
+/// `struct io_event` from `<linux/aio_abi.h>`.
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct IoEvent {
+    data: u64,
+    obj: u64,
+    res: i64,
+    res2: i64,
+}
+
+/// A transfer and the iocb the kernel references while it is in flight.
+/// Boxed so the iocb address stays stable across map rehashes.
+struct AioOp {
+    op: InflightOp,
+    iocb: Iocb,
+}
+
+impl AioOp {
+    /// Point the iocb at the untransferred remainder of the op.
+    fn prepare(&mut self) {
+        let op = &self.op;
+        self.iocb = Iocb {
+            aio_data: op.op.token.0,
+            aio_lio_opcode: match op.op.dir {
+                XferDir::Write => IOCB_CMD_PWRITE,
+                XferDir::Read => IOCB_CMD_PREAD,
+            },
+            aio_fildes: op.op.fd as u32,
+            aio_buf: op.op.buf as u64 + op.done as u64,
+            aio_nbytes: (op.op.len - op.done) as u64,
+            aio_offset: op.op.offset + op.done,
+            ..Default::default()
+        };
+    }
+}
+
+pub(crate) struct AioEngine {
+    ctx: u64,
+    inflight: Mutex<HashMap<u64, Box<AioOp>>>,
+}
+
+impl AioEngine {
+    pub(crate) fn new(queue_depth: usize) -> Result<Self, IorError> {
+        let mut ctx: u64 = 0;
+        let rc = unsafe {
+            libc::syscall(
+                libc::SYS_io_setup,
+                queue_depth.max(1) as c_long,
+                &mut ctx as *mut u64,
+            )
+        };
+        if rc < 0 {
+            return Err(IorError::Io(errno()));
+        }
+        Ok(Self {
+            ctx,
+            inflight: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub(crate) fn submit(&self, op: PendingOp) -> Result<(), IorError> {
+        let mut inflight = self.inflight.lock().unwrap();
+        let mut aop = Box::new(AioOp {
+            op: InflightOp::new(op),
+            iocb: Iocb::default(),
+        });
+        aop.prepare();
+        io_submit(self.ctx, &mut aop.iocb).map_err(IorError::Io)?;
+        inflight.insert(aop.op.op.token.0, aop);
+        Ok(())
+    }
+
+    pub(crate) fn poll(&self, max_completions: usize) -> Result<usize, IorError> {
+        let mut batch = Vec::new();
+        {
+            let mut inflight = self.inflight.lock().unwrap();
+            let nr = max_completions.min(inflight.len());
+            if nr == 0 {
+                return Ok(0);
+            }
+
+            let mut events = vec![IoEvent::default(); nr];
+            let timeout = libc::timespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            };
+            let rc = unsafe {
+                libc::syscall(
+                    libc::SYS_io_getevents,
+                    self.ctx,
+                    0 as c_long,
+                    nr as c_long,
+                    events.as_mut_ptr(),
+                    &timeout as *const libc::timespec,
+                )
+            };
+            if rc < 0 {
+                let err = errno();
+                if err == libc::EINTR {
+                    return Ok(0);
+                }
+                return Err(IorError::Io(err));
+            }
+
+            for ev in &events[..rc as usize] {
+                let Some(mut aop) = inflight.remove(&ev.data) else {
+                    continue;
+                };
+                if ev.res < 0 {
+                    batch.push(aop.op.complete(-1, -ev.res as i32));
+                } else if aop.op.advance(ev.res) {
+                    aop.prepare();
+                    match io_submit(self.ctx, &mut aop.iocb) {
+                        Ok(()) => {
+                            inflight.insert(ev.data, aop);
+                        }
+                        Err(err) => batch.push(aop.op.complete(-1, err)),
+                    }
+                } else {
+                    let done = aop.op.done;
+                    batch.push(aop.op.complete(done, 0));
+                }
+            }
+        }
+
+        // Fire callbacks on the polling (caller) thread, outside the lock
+        for cop in &batch {
+            (cop.callback)(&cop.result);
+        }
+        Ok(batch.len())
+    }
+
+    /// Cancel an in-flight transfer via `io_cancel`. If the kernel cancels it
+    /// synchronously the callback fires here with ECANCELED. Linux cannot
+    /// cancel regular-file or O_DIRECT iocbs (EINVAL), and EINPROGRESS or
+    /// EAGAIN mean the op is already finishing; in those cases the transfer
+    /// is left to complete and its callback fires through `poll` as usual.
+    pub(crate) fn cancel(&self, token: XferToken) -> Result<(), IorError> {
+        let mut inflight = self.inflight.lock().unwrap();
+        let aop = inflight.get_mut(&token.0).ok_or(IorError::NotFound)?;
+
+        let mut ev = IoEvent::default();
+        let rc = unsafe {
+            libc::syscall(
+                libc::SYS_io_cancel,
+                self.ctx,
+                &mut aop.iocb as *mut Iocb,
+                &mut ev as *mut IoEvent,
+            )
+        };
+        if rc < 0 {
+            return match errno() {
+                libc::EINVAL | libc::EINPROGRESS | libc::EAGAIN => Ok(()),
+                err => Err(IorError::Io(err)),
+            };
+        }
+
+        let aop = inflight.remove(&token.0).unwrap();
+        drop(inflight);
+        let done = aop.op.done;
+        let cop = aop.op.complete(done, libc::ECANCELED);
+        (cop.callback)(&cop.result);
+        Ok(())
+    }
+}
+
+impl Drop for AioEngine {
+    fn drop(&mut self) {
+        // io_destroy waits for outstanding requests before tearing down
+        unsafe {
+            libc::syscall(libc::SYS_io_destroy, self.ctx);
+        }
+    }
+}
+
+/// Submit a single iocb, returning the errno on failure.
+fn io_submit(ctx: u64, iocb: &mut Iocb) -> Result<(), i32> {
+    let mut iocbs = [iocb as *mut Iocb];
+    let rc = unsafe { libc::syscall(libc::SYS_io_submit, ctx, 1 as c_long, iocbs.as_mut_ptr()) };
+    match rc {
+        1 => Ok(()),
+        0 => Err(libc::EAGAIN),
+        _ => Err(errno()),
+    }
+}
+
+fn errno() -> i32 {
+    unsafe { *libc::__errno_location() }
+}
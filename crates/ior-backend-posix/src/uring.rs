@@ -0,0 +1,139 @@
+//! io_uring async engine.
+//!
+//! Transfers are pushed onto the submission queue by `submit` and handed to
+//! the kernel in batches on the next `poll`, which also reaps completions
+//! without blocking. Short transfers are resubmitted for the remainder, like
+//! the retry loop in `execute_posix_io`.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use io_uring::{opcode, squeue, types, IoUring};
+
+use ior_core::error::IorError;
+use ior_core::handle::{XferDir, XferToken};
+
+use crate::{CompletedOp, InflightOp, PendingOp};
+
+/// `user_data` tag for cancel SQEs; real tokens count up from 1.
+const CANCEL_USER_DATA: u64 = u64::MAX;
+
+/// Largest single read/write the kernel will transfer (`MAX_RW_COUNT`).
+const MAX_CHUNK: i64 = 0x7fff_f000;
+
+struct UringState {
+    ring: IoUring,
+    inflight: HashMap<u64, InflightOp>,
+    /// Completions reaped from the CQ but not yet dispatched (poll limit).
+    ready: VecDeque<CompletedOp>,
+}
+
+pub(crate) struct UringEngine {
+    state: Mutex<UringState>,
+}
+
+impl UringEngine {
+    pub(crate) fn new(queue_depth: usize) -> Result<Self, IorError> {
+        // Room for one cancel SQE per in-flight transfer.
+        let entries = (queue_depth.max(1) * 2).next_power_of_two() as u32;
+        let ring = IoUring::new(entries)?;
+        Ok(Self {
+            state: Mutex::new(UringState {
+                ring,
+                inflight: HashMap::new(),
+                ready: VecDeque::new(),
+            }),
+        })
+    }
+
+    pub(crate) fn submit(&self, op: PendingOp) -> Result<(), IorError> {
+        let mut state = self.state.lock().unwrap();
+        let op = InflightOp::new(op);
+        let sqe = build_sqe(&op);
+        push_sqe(&mut state.ring, &sqe)?;
+        state.inflight.insert(op.op.token.0, op);
+        Ok(())
+    }
+
+    pub(crate) fn poll(&self, max_completions: usize) -> Result<usize, IorError> {
+        let batch = {
+            let mut state = self.state.lock().unwrap();
+            let state = &mut *state;
+            state.ring.submit()?;
+
+            let cqes: Vec<(u64, i32)> = state
+                .ring
+                .completion()
+                .map(|cqe| (cqe.user_data(), cqe.result()))
+                .collect();
+
+            for (user_data, res) in cqes {
+                if user_data == CANCEL_USER_DATA {
+                    continue;
+                }
+                let Some(mut op) = state.inflight.remove(&user_data) else {
+                    continue;
+                };
+                if res < 0 {
+                    let errno = -res;
+                    let bytes = if errno == libc::ECANCELED { op.done } else { -1 };
+                    state.ready.push_back(op.complete(bytes, errno));
+                } else if op.advance(res as i64) {
+                    let sqe = build_sqe(&op);
+                    push_sqe(&mut state.ring, &sqe)?;
+                    state.inflight.insert(user_data, op);
+                } else {
+                    let done = op.done;
+                    state.ready.push_back(op.complete(done, 0));
+                }
+            }
+
+            let count = state.ready.len().min(max_completions);
+            state.ready.drain(..count).collect::<Vec<_>>()
+        };
+
+        // Fire callbacks on the polling (caller) thread, outside the lock
+        for cop in &batch {
+            (cop.callback)(&cop.result);
+        }
+        Ok(batch.len())
+    }
+
+    /// Request cancellation of an in-flight transfer. The ECANCELED
+    /// completion (if the kernel manages to cancel it) is delivered by `poll`.
+    pub(crate) fn cancel(&self, token: XferToken) -> Result<(), IorError> {
+        let mut state = self.state.lock().unwrap();
+        if !state.inflight.contains_key(&token.0) {
+            return Err(IorError::NotFound);
+        }
+        let sqe = opcode::AsyncCancel::new(token.0)
+            .build()
+            .user_data(CANCEL_USER_DATA);
+        push_sqe(&mut state.ring, &sqe)?;
+        state.ring.submit()?;
+        Ok(())
+    }
+}
+
+/// Build a read/write SQE for the untransferred remainder of `op`.
+fn build_sqe(op: &InflightOp) -> squeue::Entry {
+    let fd = types::Fd(op.op.fd);
+    let buf = unsafe { op.op.buf.add(op.done as usize) };
+    let len = (op.op.len - op.done).min(MAX_CHUNK) as u32;
+    let offset = (op.op.offset + op.done) as u64;
+    let sqe = match op.op.dir {
+        XferDir::Write => opcode::Write::new(fd, buf, len).offset(offset).build(),
+        XferDir::Read => opcode::Read::new(fd, buf, len).offset(offset).build(),
+    };
+    sqe.user_data(op.op.token.0)
+}
+
+/// Push an SQE, flushing the submission queue to the kernel if it is full.
+fn push_sqe(ring: &mut IoUring, sqe: &squeue::Entry) -> Result<(), IorError> {
+    // Safety: the buffer stays valid until the callback fires (Aiori contract).
+    if unsafe { ring.submission().push(sqe) }.is_ok() {
+        return Ok(());
+    }
+    ring.submit()?;
+    unsafe { ring.submission().push(sqe) }.map_err(|_| IorError::Io(libc::EAGAIN))
+}
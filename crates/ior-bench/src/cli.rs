@@ -1,17 +1,19 @@
 use clap::Parser;
-use ior_core::IorParam;
+use ior_core::{AccessLayout, DataPacketType, IorParam, ParamError};
+
+use crate::sweep::SweepAxis;
 
 /// Rust IOR benchmark — MPI-parallel I/O performance tester.
 ///
 /// Reference: `parse_options.c:412-486`
-#[derive(Parser, Debug)]
-#[command(name = "ior-bench", about = "Async IOR benchmark")]
+#[derive(Parser, Debug, Clone)]
+#[command(name = "ior-bench", about = "Async IOR benchmark", args_override_self = true)]
 pub struct CliArgs {
     /// I/O backend API
     #[arg(short = 'a', long = "api", default_value = "POSIX")]
     pub api: String,
 
-    /// Block size per task (supports k/m/g suffixes)
+    /// Block size per task (supports k/m/g suffixes; a comma list sweeps)
     #[arg(short = 'b', long = "block-size", default_value = "1m")]
     pub block_size: String,
 
@@ -19,7 +21,7 @@ pub struct CliArgs {
     #[arg(short = 's', long = "segment-count", default_value_t = 1)]
     pub segment_count: i64,
 
-    /// Transfer size per I/O operation (supports k/m/g suffixes)
+    /// Transfer size per I/O operation (supports k/m/g suffixes; a comma list sweeps)
     #[arg(short = 't', long = "transfer-size", default_value = "256k")]
     pub transfer_size: String,
 
@@ -51,6 +53,10 @@ pub struct CliArgs {
     #[arg(short = 'z', long = "random-offset")]
     pub random_offset: bool,
 
+    /// Shared-file layout: segmented (a block per rank) or strided (interleaved transfers)
+    #[arg(long = "layout", default_value = "segmented")]
+    pub layout: String,
+
     /// Number of repetitions
     #[arg(short = 'i', long = "repetitions", default_value_t = 1)]
     pub repetitions: i32,
@@ -63,6 +69,18 @@ pub struct CliArgs {
     #[arg(short = 'D', long = "deadline", default_value_t = 0)]
     pub deadline_for_stonewalling: i32,
 
+    /// After a stonewall, continue every rank to the max pairs of any rank
+    #[arg(long = "stonewall-wear-out")]
+    pub stonewall_wear_out: bool,
+
+    /// Stop each rank after this many transfers (0 = unlimited)
+    #[arg(long = "stonewall-wear-out-iterations", default_value_t = 0)]
+    pub stonewall_wear_out_iterations: u64,
+
+    /// Record bandwidth per interval during each phase (e.g. 100ms, 1s)
+    #[arg(long = "sample-interval")]
+    pub sample_interval: Option<String>,
+
     /// fsync() after write phase
     #[arg(short = 'e', long = "fsync")]
     pub fsync: bool,
@@ -103,6 +121,10 @@ pub struct CliArgs {
     #[arg(short = 'Z', long = "reorder-tasks-random")]
     pub reorder_tasks_random: bool,
 
+    /// Nodes to shift by for -C (tasks per node × this value)
+    #[arg(short = 'Q', long = "task-per-node-offset", default_value_t = 1)]
+    pub task_per_node_offset: i32,
+
     /// Enable intra-test barriers
     #[arg(short = 'g', long = "intra-test-barriers")]
     pub intra_test_barriers: bool,
@@ -115,6 +137,18 @@ pub struct CliArgs {
     #[arg(short = 'q', long = "queue-depth", default_value_t = 1)]
     pub queue_depth: i32,
 
+    /// Run the tests in a C IOR script file (IOR START / RUN / IOR STOP)
+    #[arg(short = 'f', long = "script")]
+    pub script: Option<String>,
+
+    /// Sweep a setting over a list of values, e.g. queue_depth=1,2,4,8 (repeatable)
+    #[arg(long = "sweep")]
+    pub sweep: Vec<String>,
+
+    /// Workload file (TOML, or YAML by extension); explicit flags override it
+    #[arg(long = "config")]
+    pub config: Option<String>,
+
     /// Output results as JSON to stdout (suppresses text output)
     #[arg(long = "json")]
     pub json: bool,
@@ -123,16 +157,42 @@ pub struct CliArgs {
     #[arg(long = "json-file")]
     pub json_file: Option<String>,
 
+    /// Data packet type: timestamp, offset, random, incompressible (or t/o/r/i)
+    #[arg(short = 'l', long = "data-packet-type", default_value = "timestamp")]
+    pub data_packet_type: String,
+
+    /// Percentage of each 4 KiB block that compresses away (0-100)
+    #[arg(
+        long = "compress-percentage",
+        default_value_t = 0,
+        value_parser = clap::value_parser!(u8).range(0..=100),
+        conflicts_with = "data_packet_type"
+    )]
+    pub compress_percentage: u8,
+
+    /// Percentage of 4 KiB blocks that are duplicates (0-100)
+    #[arg(
+        long = "dedupe-percentage",
+        default_value_t = 0,
+        value_parser = clap::value_parser!(u8).range(0..=100),
+        conflicts_with = "data_packet_type"
+    )]
+    pub dedupe_percentage: u8,
+
     /// Timestamp signature value (seed for data pattern, C IOR: -G)
     #[arg(short = 'G', long = "timestamp-signature", default_value_t = 0)]
     pub timestamp_signature: i32,
+
+    /// Run N ranks as threads of this process instead of MPI processes
+    #[arg(long = "threads", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub threads: Option<usize>,
 }
 
 /// Parse a size string with optional k/m/g/t suffix (case-insensitive).
-pub fn parse_size(s: &str) -> i64 {
+pub fn parse_size(s: &str) -> Result<i64, ParamError> {
     let s = s.trim();
     if s.is_empty() {
-        return 0;
+        return Ok(0);
     }
 
     let (num_str, multiplier) = match s.as_bytes().last() {
@@ -146,19 +206,79 @@ pub fn parse_size(s: &str) -> i64 {
     num_str
         .trim()
         .parse::<i64>()
-        .unwrap_or_else(|_| panic!("invalid size: {s}"))
-        * multiplier
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| ParamError::InvalidValue {
+            name: "size",
+            value: s.to_string(),
+        })
+}
+
+/// Parse a duration such as `100ms`, `2s`, `500us` or a bare number of
+/// seconds into seconds.
+pub fn parse_duration(s: &str) -> Result<f64, ParamError> {
+    let s = s.trim();
+    let (num_str, scale) = if let Some(n) = s.strip_suffix("ms") {
+        (n, 1e-3)
+    } else if let Some(n) = s.strip_suffix("us") {
+        (n, 1e-6)
+    } else if let Some(n) = s.strip_suffix('s') {
+        (n, 1.0)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 60.0)
+    } else {
+        (s, 1.0)
+    };
+
+    match num_str.trim().parse::<f64>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => Ok(value * scale),
+        _ => Err(ParamError::InvalidValue {
+            name: "duration",
+            value: s.to_string(),
+        }),
+    }
 }
 
 impl CliArgs {
-    /// Convert CLI arguments to an IorParam struct.
-    pub fn into_ior_param(self) -> IorParam {
+    /// Collect the sweep axes: comma lists in `-b`/`-t` first, then each
+    /// `--sweep`. List options are left holding their first value.
+    pub fn take_sweep_axes(&mut self) -> Result<Vec<SweepAxis>, String> {
+        let mut axes = Vec::new();
+        for (key, value) in [
+            ("block_size", &mut self.block_size),
+            ("transfer_size", &mut self.transfer_size),
+        ] {
+            if value.contains(',') {
+                let axis = SweepAxis::new(key, value)?;
+                *value = axis.values[0].clone();
+                axes.push(axis);
+            }
+        }
+        for spec in &self.sweep {
+            axes.push(SweepAxis::parse(spec)?);
+        }
+        Ok(axes)
+    }
+
+    /// Convert CLI arguments to an IorParam struct, collecting every value
+    /// that does not parse.
+    pub fn into_ior_param(self) -> Result<IorParam, Vec<ParamError>> {
         let mut params = IorParam::default();
+        let mut errors = Vec::new();
+        let mut size = |name, value: &str| {
+            parse_size(value).unwrap_or_else(|_| {
+                errors.push(ParamError::InvalidValue {
+                    name,
+                    value: value.to_string(),
+                });
+                0
+            })
+        };
 
         params.set_api(&self.api);
-        params.block_size = parse_size(&self.block_size);
+        params.block_size = size("block size", &self.block_size);
         params.segment_count = self.segment_count;
-        params.transfer_size = parse_size(&self.transfer_size);
+        params.transfer_size = size("transfer size", &self.transfer_size);
         params.set_test_file_name(&self.test_file);
 
         // If neither -r nor -w specified, default to both
@@ -174,9 +294,27 @@ impl CliArgs {
         params.check_read = self.check_read;
         params.file_per_proc = self.file_per_proc;
         params.random_offset = self.random_offset;
+        match AccessLayout::parse(&self.layout) {
+            Some(layout) => params.layout = layout,
+            None => errors.push(ParamError::InvalidValue {
+                name: "layout",
+                value: self.layout.clone(),
+            }),
+        }
         params.repetitions = self.repetitions;
         params.inter_test_delay = self.inter_test_delay;
         params.deadline_for_stonewalling = self.deadline_for_stonewalling;
+        params.stonewall_wear_out = self.stonewall_wear_out;
+        params.stonewall_wear_out_iterations = self.stonewall_wear_out_iterations;
+        if let Some(interval) = self.sample_interval.as_deref() {
+            match parse_duration(interval) {
+                Ok(secs) if secs > 0.0 => params.sample_interval = secs,
+                _ => errors.push(ParamError::InvalidValue {
+                    name: "sample interval",
+                    value: interval.to_string(),
+                }),
+            }
+        }
         params.fsync = self.fsync;
         params.fsync_per_write = self.fsync_per_write;
         params.verbose = self.verbose as i32;
@@ -187,12 +325,30 @@ impl CliArgs {
         params.num_tasks = self.num_tasks;
         params.reorder_tasks = self.reorder_tasks;
         params.reorder_tasks_random = self.reorder_tasks_random;
+        params.task_per_node_offset = self.task_per_node_offset;
         params.intra_test_barriers = self.intra_test_barriers;
         params.direct_io = self.direct_io;
         params.queue_depth = self.queue_depth;
         params.time_stamp_signature_value = self.timestamp_signature;
+        match DataPacketType::parse(&self.data_packet_type) {
+            Some(kind) => params.data_packet_type = kind,
+            None => errors.push(ParamError::InvalidValue {
+                name: "data packet type",
+                value: self.data_packet_type.clone(),
+            }),
+        }
+        if self.compress_percentage > 0 || self.dedupe_percentage > 0 {
+            params.data_packet_type = DataPacketType::Tunable {
+                compress: self.compress_percentage,
+                dedupe: self.dedupe_percentage,
+            };
+        }
 
-        params
+        if errors.is_empty() {
+            Ok(params)
+        } else {
+            Err(errors)
+        }
     }
 }
 
@@ -202,13 +358,37 @@ mod tests {
 
     #[test]
     fn test_parse_size() {
-        assert_eq!(parse_size("1024"), 1024);
-        assert_eq!(parse_size("1k"), 1024);
-        assert_eq!(parse_size("1K"), 1024);
-        assert_eq!(parse_size("1m"), 1_048_576);
-        assert_eq!(parse_size("1M"), 1_048_576);
-        assert_eq!(parse_size("1g"), 1_073_741_824);
-        assert_eq!(parse_size("4k"), 4096);
-        assert_eq!(parse_size("256k"), 262_144);
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("1k"), Ok(1024));
+        assert_eq!(parse_size("1K"), Ok(1024));
+        assert_eq!(parse_size("1m"), Ok(1_048_576));
+        assert_eq!(parse_size("1M"), Ok(1_048_576));
+        assert_eq!(parse_size("1g"), Ok(1_073_741_824));
+        assert_eq!(parse_size("4k"), Ok(4096));
+        assert_eq!(parse_size("256k"), Ok(262_144));
+        assert!(parse_size("4q").is_err());
+        assert!(parse_size("k").is_err());
+        assert!(parse_size("99999999t").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("100ms"), Ok(0.1));
+        assert_eq!(parse_duration("2s"), Ok(2.0));
+        assert_eq!(parse_duration("1.5"), Ok(1.5));
+        assert_eq!(parse_duration("1m"), Ok(60.0));
+        assert!((parse_duration("250us").unwrap() - 0.00025).abs() < 1e-12);
+        assert!(parse_duration("-1s").is_err());
+        assert!(parse_duration("fast").is_err());
+    }
+
+    #[test]
+    fn test_into_ior_param_collects_errors() {
+        let args = CliArgs::parse_from(["ior-bench", "-b", "1x", "-t", "2y", "-l", "zzz"]);
+        let Err(errors) = args.into_ior_param() else {
+            panic!("invalid sizes accepted");
+        };
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].to_string(), "invalid block size: '1x'");
     }
 }
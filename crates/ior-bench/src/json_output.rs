@@ -1,8 +1,10 @@
 use serde::Serialize;
 
-use crate::report::{IterResult, SummaryStats};
+use crate::report::{IterResult, LatencyPercentiles, StonewallResult, SummaryStats, TimeSeries};
 use crate::runner::BenchmarkResults;
+use crate::verify::MismatchReport;
 use ior_core::params::IorParam;
+use ior_core::{DataPacketType, MismatchKind};
 
 const MEBIBYTE: f64 = 1_048_576.0;
 const KIBIBYTE: f64 = 1024.0;
@@ -44,6 +46,15 @@ pub struct IorJsonParameters {
     pub segment_count: i64,
     #[serde(rename = "numTasks")]
     pub num_tasks: i32,
+    #[serde(rename = "numNodes")]
+    pub num_nodes: i32,
+    /// Tasks on node 0 (C IOR's tasksPerNode)
+    #[serde(rename = "tasksPerNode")]
+    pub tasks_per_node: i32,
+    #[serde(rename = "reorderTasks")]
+    pub reorder_tasks: bool,
+    #[serde(rename = "taskPerNodeOffset")]
+    pub task_per_node_offset: i32,
     pub repetitions: i32,
     #[serde(rename = "filePerProc")]
     pub file_per_proc: bool,
@@ -55,11 +66,24 @@ pub struct IorJsonParameters {
     pub test_file_name: String,
     #[serde(rename = "deadlineForStonewalling")]
     pub deadline_for_stonewalling: i32,
+    #[serde(rename = "stoneWallingWearOut")]
+    pub stonewall_wear_out: bool,
+    #[serde(rename = "stoneWallingWearOutIterations")]
+    pub stonewall_wear_out_iterations: u64,
+    #[serde(rename = "sampleInterval")]
+    pub sample_interval: f64,
     #[serde(rename = "keepFile")]
     pub keep_file: bool,
     pub fsync: bool,
     #[serde(rename = "randomOffset")]
     pub random_offset: bool,
+    pub layout: String,
+    #[serde(rename = "dataPacketType")]
+    pub data_packet_type: String,
+    #[serde(rename = "compressPercentage")]
+    pub compress_percentage: u8,
+    #[serde(rename = "dedupePercentage")]
+    pub dedupe_percentage: u8,
 }
 
 #[derive(Serialize)]
@@ -102,11 +126,92 @@ pub struct IorJsonResult {
     #[serde(rename = "numTasks")]
     pub num_tasks: i32,
     pub iter: i32,
+    /// Phase was cut short by max_time_duration
+    pub truncated: bool,
+    #[serde(rename = "latencyPercentiles")]
+    pub latency_percentiles: IorJsonLatency,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stonewall: Option<IorJsonStonewall>,
+    #[serde(rename = "timeSeries", skip_serializing_if = "Option::is_none")]
+    pub time_series: Option<IorJsonTimeSeries>,
+    /// Data verification errors of this iteration (`-W`/`-R` only)
+    #[serde(rename = "verifyErrors", skip_serializing_if = "Option::is_none")]
+    pub verify_errors: Option<u64>,
+    /// First mismatching words of each rank
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mismatches: Vec<IorJsonMismatch>,
+}
+
+/// One mismatching word found by `-W`/`-R`. Words are hex strings since
+/// JSON numbers cannot hold every 64-bit value.
+#[derive(Serialize)]
+pub struct IorJsonMismatch {
+    pub rank: i32,
+    #[serde(rename = "expectedRank")]
+    pub expected_rank: i32,
+    pub file: String,
+    pub offset: i64,
+    #[serde(rename = "transferOffset")]
+    pub transfer_offset: i64,
+    pub word: usize,
+    pub expected: String,
+    pub actual: String,
+    /// `zero`, `otherRank`, `stale` or `unknown`
+    pub kind: &'static str,
+    /// Rank whose stamp was found (`otherRank` only)
+    #[serde(rename = "sourceRank", skip_serializing_if = "Option::is_none")]
+    pub source_rank: Option<i32>,
+}
+
+/// Aggregate bandwidth per `--sample-interval` across all ranks.
+#[derive(Serialize)]
+pub struct IorJsonTimeSeries {
+    #[serde(rename = "intervalSec")]
+    pub interval_sec: f64,
+    pub samples: Vec<IorJsonSample>,
+}
+
+#[derive(Serialize)]
+pub struct IorJsonSample {
+    /// End of the interval, seconds from the phase start
+    pub time: f64,
+    #[serde(rename = "bwMiB")]
+    pub bw_mib: f64,
+    #[serde(rename = "dataMiB")]
+    pub data_mib: f64,
+}
+
+/// Per-transfer latency percentiles in seconds, merged across ranks.
+#[derive(Serialize)]
+pub struct IorJsonLatency {
+    pub samples: u64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    #[serde(rename = "p99.9")]
+    pub p999: f64,
+    pub max: f64,
+}
+
+/// Bandwidth up to the stonewall; the enclosing result covers the wear-out.
+#[derive(Serialize)]
+pub struct IorJsonStonewall {
+    #[serde(rename = "bwMiB")]
+    pub bw_mib: f64,
+    pub time: f64,
+    #[serde(rename = "pairsAccessedMin")]
+    pub pairs_min: u64,
+    #[serde(rename = "pairsAccessedMax")]
+    pub pairs_max: u64,
+    #[serde(rename = "dataMiB")]
+    pub data_mib: f64,
 }
 
 #[derive(Serialize)]
 pub struct IorJsonSummary {
     pub operation: String,
+    #[serde(rename = "TestID")]
+    pub test_id: i32,
     #[serde(rename = "bwMaxMIB")]
     pub bw_max_mib: f64,
     #[serde(rename = "bwMinMIB")]
@@ -131,29 +236,82 @@ pub struct IorJsonSummary {
 // Builder
 // ============================================================================
 
-pub fn build_ior_json(
-    params: &IorParam,
-    results: &BenchmarkResults,
-    command_line: &str,
-) -> IorJsonDocument {
-    let began = current_time_string();
+/// One completed test (a `-f` script `RUN`, or the single command-line test).
+pub struct TestRun {
+    pub params: IorParam,
+    pub results: BenchmarkResults,
+    pub start_time: String,
+}
+
+pub fn build_ior_json(runs: &[TestRun], command_line: &str) -> IorJsonDocument {
+    let began = runs
+        .first()
+        .map_or_else(current_time_string, |run| run.start_time.clone());
     let machine = get_machine_string();
 
+    let mut tests = Vec::new();
+    let mut summary = Vec::new();
+    for (test_id, run) in runs.iter().enumerate() {
+        let test_id = test_id as i32;
+        tests.push(build_test(test_id, run));
+
+        let results = &run.results;
+        if !results.write_results.is_empty() {
+            summary.push(build_summary("write", test_id, &results.write_results));
+        }
+        if !results.read_results.is_empty() {
+            summary.push(build_summary("read", test_id, &results.read_results));
+        }
+    }
+
+    let finished = current_time_string();
+
+    IorJsonDocument {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        began,
+        command_line: command_line.to_string(),
+        machine,
+        tests,
+        summary,
+        finished,
+    }
+}
+
+fn build_test(test_id: i32, run: &TestRun) -> IorJsonTest {
+    let params = &run.params;
+    let results = &run.results;
+
+    let (compress_percentage, dedupe_percentage) = match params.data_packet_type {
+        DataPacketType::Tunable { compress, dedupe } => (compress, dedupe),
+        _ => (0, 0),
+    };
+
     let parameters = IorJsonParameters {
         api: params.api_str().to_string(),
         block_size: params.block_size,
         transfer_size: params.transfer_size,
         segment_count: params.segment_count,
         num_tasks: params.num_tasks,
+        num_nodes: params.num_nodes,
+        tasks_per_node: params.num_tasks_on_node0,
+        reorder_tasks: params.reorder_tasks,
+        task_per_node_offset: params.task_per_node_offset,
         repetitions: params.repetitions,
         file_per_proc: params.file_per_proc,
         direct_io: params.direct_io,
         queue_depth: params.queue_depth,
         test_file_name: params.test_file_name_str().to_string(),
         deadline_for_stonewalling: params.deadline_for_stonewalling,
+        stonewall_wear_out: params.stonewall_wear_out,
+        stonewall_wear_out_iterations: params.stonewall_wear_out_iterations,
+        sample_interval: params.sample_interval,
         keep_file: params.keep_file,
         fsync: params.fsync,
         random_offset: params.random_offset,
+        layout: params.layout.as_str().to_string(),
+        data_packet_type: params.data_packet_type.as_str().to_string(),
+        compress_percentage,
+        dedupe_percentage,
     };
 
     let agg_file_size = params.expected_agg_file_size();
@@ -190,33 +348,12 @@ pub fn build_ior_json(
         }
     }
 
-    let test = IorJsonTest {
-        test_id: 0,
-        start_time: began.clone(),
+    IorJsonTest {
+        test_id,
+        start_time: run.start_time.clone(),
         parameters,
         options,
         results: json_results,
-    };
-
-    // Build summary
-    let mut summary = Vec::new();
-    if !results.write_results.is_empty() {
-        summary.push(build_summary("write", &results.write_results));
-    }
-    if !results.read_results.is_empty() {
-        summary.push(build_summary("read", &results.read_results));
-    }
-
-    let finished = current_time_string();
-
-    IorJsonDocument {
-        version: env!("CARGO_PKG_VERSION").to_string(),
-        began,
-        command_line: command_line.to_string(),
-        machine,
-        tests: vec![test],
-        summary,
-        finished,
     }
 }
 
@@ -234,10 +371,75 @@ fn iter_result_to_json(access: &str, r: &IterResult, params: &IorParam) -> IorJs
         total_time: r.total_time,
         num_tasks: params.num_tasks,
         iter: r.rep,
+        truncated: r.truncated,
+        latency_percentiles: latency_to_json(&r.latency_hist),
+        stonewall: r.stonewall.as_ref().map(stonewall_to_json),
+        time_series: r.time_series.as_ref().map(time_series_to_json),
+        verify_errors: r.verify_errors,
+        mismatches: r.mismatches.iter().map(mismatch_to_json).collect(),
+    }
+}
+
+fn mismatch_to_json(m: &MismatchReport) -> IorJsonMismatch {
+    let (kind, source_rank) = match m.kind {
+        MismatchKind::Zero => ("zero", None),
+        MismatchKind::OtherRank(rank) => ("otherRank", Some(rank)),
+        MismatchKind::Stale => ("stale", None),
+        MismatchKind::Unknown => ("unknown", None),
+    };
+    IorJsonMismatch {
+        rank: m.rank,
+        expected_rank: m.expected_rank,
+        file: m.file.clone(),
+        offset: m.offset(),
+        transfer_offset: m.transfer_offset,
+        word: m.word,
+        expected: format!("{:#018x}", m.expected),
+        actual: format!("{:#018x}", m.actual),
+        kind,
+        source_rank,
     }
 }
 
-fn build_summary(operation: &str, results: &[IterResult]) -> IorJsonSummary {
+fn time_series_to_json(ts: &TimeSeries) -> IorJsonTimeSeries {
+    let samples = ts
+        .bytes
+        .iter()
+        .enumerate()
+        .map(|(i, &bytes)| IorJsonSample {
+            time: (i + 1) as f64 * ts.interval,
+            bw_mib: bytes as f64 / MEBIBYTE / ts.interval,
+            data_mib: bytes as f64 / MEBIBYTE,
+        })
+        .collect();
+    IorJsonTimeSeries {
+        interval_sec: ts.interval,
+        samples,
+    }
+}
+
+fn latency_to_json(lat: &LatencyPercentiles) -> IorJsonLatency {
+    IorJsonLatency {
+        samples: lat.samples,
+        p50: lat.p50,
+        p90: lat.p90,
+        p99: lat.p99,
+        p999: lat.p999,
+        max: lat.max,
+    }
+}
+
+fn stonewall_to_json(sw: &StonewallResult) -> IorJsonStonewall {
+    IorJsonStonewall {
+        bw_mib: sw.bw / MEBIBYTE,
+        time: sw.time,
+        pairs_min: sw.pairs_min,
+        pairs_max: sw.pairs_max,
+        data_mib: sw.data_moved as f64 / MEBIBYTE,
+    }
+}
+
+fn build_summary(operation: &str, test_id: i32, results: &[IterResult]) -> IorJsonSummary {
     let bw_values: Vec<f64> = results.iter().map(|r| r.bw / MEBIBYTE).collect();
     let bw_stats = SummaryStats::from_values(&bw_values);
 
@@ -249,6 +451,7 @@ fn build_summary(operation: &str, results: &[IterResult]) -> IorJsonSummary {
 
     IorJsonSummary {
         operation: operation.to_string(),
+        test_id,
         bw_max_mib: bw_stats.max,
         bw_min_mib: bw_stats.min,
         bw_mean_mib: bw_stats.mean,
@@ -320,6 +523,14 @@ pub fn get_machine_string() -> String {
 mod tests {
     use super::*;
 
+    fn single_run(params: IorParam, results: BenchmarkResults) -> TestRun {
+        TestRun {
+            params,
+            results,
+            start_time: "Wed Feb 19 12:00:00 2026".to_string(),
+        }
+    }
+
     #[test]
     fn test_format_size() {
         assert_eq!(format_size(512), "512 bytes");
@@ -342,14 +553,176 @@ mod tests {
                 total_time: 0.52,
                 data_moved: 104857600,
                 rep: 0,
+                stonewall: None,
+                truncated: false,
+                latency_hist: LatencyPercentiles {
+                    samples: 400,
+                    p50: 0.001,
+                    p90: 0.002,
+                    p99: 0.004,
+                    p999: 0.008,
+                    max: 0.01,
+                },
+                time_series: None,
+                verify_errors: Some(1),
+                mismatches: vec![MismatchReport {
+                    rank: 1,
+                    expected_rank: 2,
+                    file: "testFile".into(),
+                    transfer_offset: 4096,
+                    word: 3,
+                    expected: (2 << 32) | 3,
+                    actual: (1 << 32) | 3,
+                    kind: MismatchKind::OtherRank(1),
+                }],
             }],
             read_results: vec![],
         };
 
-        let doc = build_ior_json(&params, &results, "ior-bench -w");
+        let doc = build_ior_json(&[single_run(params, results)], "ior-bench -w");
         let json = serde_json::to_string_pretty(&doc).unwrap();
         assert!(json.contains("\"version\""));
         assert!(json.contains("\"write\""));
         assert!(json.contains("\"bwMiB\""));
+        assert!(!json.contains("\"stonewall\""));
+
+        let value = serde_json::to_value(&doc).unwrap();
+        let lat = &value["tests"][0]["Results"][0]["latencyPercentiles"];
+        assert_eq!(lat["samples"], 400);
+        assert_eq!(lat["p99.9"], 0.008);
+        assert!(value["tests"][0]["Results"][0].get("timeSeries").is_none());
+        assert_eq!(value["tests"][0]["Results"][0]["verifyErrors"], 1);
+        let mismatch = &value["tests"][0]["Results"][0]["mismatches"][0];
+        assert_eq!(mismatch["offset"], 4120);
+        assert_eq!(mismatch["expected"], "0x0000000200000003");
+        assert_eq!(mismatch["kind"], "otherRank");
+        assert_eq!(mismatch["sourceRank"], 1);
+    }
+
+    #[test]
+    fn test_stonewall_result_serializes() {
+        let params = IorParam {
+            deadline_for_stonewalling: 10,
+            stonewall_wear_out: true,
+            ..Default::default()
+        };
+        let results = BenchmarkResults {
+            write_results: vec![IterResult {
+                bw: 80.0 * MEBIBYTE,
+                iops: 320.0,
+                latency: 0.001,
+                open_time: 0.01,
+                rdwr_time: 12.5,
+                close_time: 0.01,
+                total_time: 12.52,
+                data_moved: 1000 * 1048576,
+                rep: 0,
+                truncated: false,
+                latency_hist: LatencyPercentiles::default(),
+                time_series: None,
+                verify_errors: None,
+                mismatches: Vec::new(),
+                stonewall: Some(StonewallResult {
+                    time: 10.0,
+                    pairs_min: 700,
+                    pairs_max: 1000,
+                    data_moved: 850 * 1048576,
+                    bw: 85.0 * MEBIBYTE,
+                }),
+            }],
+            read_results: vec![],
+        };
+
+        let doc = build_ior_json(&[single_run(params, results)], "ior-bench -w -D 10");
+        let value = serde_json::to_value(&doc).unwrap();
+        let sw = &value["tests"][0]["Results"][0]["stonewall"];
+        assert_eq!(sw["pairsAccessedMin"], 700);
+        assert_eq!(sw["pairsAccessedMax"], 1000);
+        assert_eq!(sw["bwMiB"], 85.0);
+        assert_eq!(value["tests"][0]["Parameters"]["stoneWallingWearOut"], true);
+    }
+
+    #[test]
+    fn test_time_series_serializes() {
+        let params = IorParam {
+            sample_interval: 0.5,
+            ..Default::default()
+        };
+        let results = BenchmarkResults {
+            write_results: vec![IterResult {
+                bw: 3.0 * MEBIBYTE,
+                iops: 12.0,
+                latency: 0.001,
+                open_time: 0.0,
+                rdwr_time: 1.0,
+                close_time: 0.0,
+                total_time: 1.0,
+                data_moved: 3 * 1048576,
+                rep: 0,
+                stonewall: None,
+                truncated: false,
+                latency_hist: LatencyPercentiles::default(),
+                time_series: Some(TimeSeries {
+                    interval: 0.5,
+                    bytes: vec![2 * 1048576, 1048576],
+                }),
+                verify_errors: None,
+                mismatches: Vec::new(),
+            }],
+            read_results: vec![],
+        };
+
+        let doc = build_ior_json(&[single_run(params, results)], "ior-bench -w --sample-interval 500ms");
+        let value = serde_json::to_value(&doc).unwrap();
+        let ts = &value["tests"][0]["Results"][0]["timeSeries"];
+        assert_eq!(ts["intervalSec"], 0.5);
+        assert_eq!(ts["samples"][0]["time"], 0.5);
+        assert_eq!(ts["samples"][0]["bwMiB"], 4.0);
+        assert_eq!(ts["samples"][1]["dataMiB"], 1.0);
+    }
+
+    #[test]
+    fn test_multiple_tests() {
+        let result = |bw: f64| IterResult {
+            bw: bw * MEBIBYTE,
+            iops: 1.0,
+            latency: 0.0,
+            open_time: 0.0,
+            rdwr_time: 1.0,
+            close_time: 0.0,
+            total_time: 1.0,
+            data_moved: 1048576,
+            rep: 0,
+            stonewall: None,
+            truncated: false,
+            latency_hist: LatencyPercentiles::default(),
+            time_series: None,
+            verify_errors: None,
+            mismatches: Vec::new(),
+        };
+        let run = |transfer_size: i64, bw: f64| {
+            single_run(
+                IorParam {
+                    transfer_size,
+                    ..Default::default()
+                },
+                BenchmarkResults {
+                    write_results: vec![result(bw)],
+                    read_results: vec![result(bw * 2.0)],
+                },
+            )
+        };
+
+        let doc = build_ior_json(&[run(262144, 10.0), run(1048576, 20.0)], "ior-bench -f script");
+        let value = serde_json::to_value(&doc).unwrap();
+        let tests = value["tests"].as_array().unwrap();
+        assert_eq!(tests.len(), 2);
+        assert_eq!(tests[1]["TestID"], 1);
+        assert_eq!(tests[1]["Parameters"]["transferSize"], 1048576);
+        let summary = value["summary"].as_array().unwrap();
+        assert_eq!(summary.len(), 4);
+        assert_eq!(summary[3]["operation"], "read");
+        assert_eq!(summary[3]["TestID"], 1);
+        assert_eq!(summary[3]["bwMeanMIB"], 40.0);
     }
 }
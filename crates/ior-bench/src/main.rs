@@ -2,146 +2,430 @@ mod cli;
 mod json_output;
 mod report;
 mod runner;
+mod sampler;
+mod script;
+mod sweep;
+mod topology;
+mod verify;
 
-use clap::Parser;
-use mpi::topology::Color;
-use mpi::traits::*;
+use ior_core::{Comm, ThreadComm};
 
 use cli::CliArgs;
 
 fn main() {
-    let universe = mpi::initialize().unwrap();
-    let world = universe.world();
+    let raw_args: Vec<String> = std::env::args().collect();
+    let (filtered_args, cli_backend_options) = ior_core::extract_backend_options(raw_args);
+    let args: CliArgs = ior_core::workload::parse_from(&filtered_args);
+
+    // --threads: ranks are threads of this process instead of MPI processes
+    #[cfg(feature = "mpi")]
+    let threads = args.threads;
+    #[cfg(not(feature = "mpi"))]
+    let threads = Some(args.threads.unwrap_or(1));
+
+    match threads {
+        Some(n) => {
+            ThreadComm::run(n, |world| {
+                run(&world, args.clone(), cli_backend_options.clone(), &filtered_args)
+            });
+        }
+        #[cfg(feature = "mpi")]
+        None => {
+            let universe = mpi::initialize().unwrap();
+            run(&universe.world(), args, cli_backend_options, &filtered_args);
+            // MPI_Finalize happens on drop of `universe`
+        }
+        #[cfg(not(feature = "mpi"))]
+        None => unreachable!(),
+    }
+}
+
+/// Run every test of the invocation on the ranks of `world`.
+fn run<C: Comm>(
+    world: &C,
+    args: CliArgs,
+    cli_backend_options: ior_core::BackendOptions,
+    filtered_args: &[String],
+) {
     let rank = world.rank();
-    let mpi_size = world.size();
+    let world_size = world.size();
 
-    let raw_args: Vec<String> = std::env::args().collect();
-    let (filtered_args, backend_options) = ior_core::extract_backend_options(raw_args);
-    let args = CliArgs::parse_from(filtered_args);
+    // Workload file (--config): one invocation per `test` entry, with the
+    // command line applied on top
+    let invocations = match args.config.as_deref() {
+        Some(path) => {
+            let parsed = ior_core::Workload::load(path)
+                .and_then(|w| {
+                    w.parse_args::<CliArgs>(filtered_args, &cli_backend_options)
+                        .map_err(|e| format!("{}: {}", path, e))
+                });
+            match parsed {
+                Ok(invocations) => invocations,
+                Err(e) => {
+                    if rank == 0 {
+                        eprintln!("ERROR: {}", e);
+                    }
+                    return;
+                }
+            }
+        }
+        None => vec![(args, cli_backend_options)],
+    };
 
     // Extract JSON flags before consuming args
-    let json_stdout = args.json;
-    let json_file = args.json_file.clone();
+    let json_stdout = invocations[0].0.json;
+    let json_file = invocations[0].0.json_file.clone();
     let json_mode = json_stdout || json_file.is_some();
     let print_text = !json_stdout;
 
     // Save command line for JSON output
     let command_line = std::env::args().collect::<Vec<_>>().join(" ");
 
-    let mut params = args.into_ior_param();
+    let mut tests = Vec::new();
+    for (mut args, backend_options) in invocations {
+        // Sweep axes (-t/-b lists, --sweep) expand every test below
+        let axes = match args.take_sweep_axes() {
+            Ok(axes) => axes,
+            Err(e) => {
+                if rank == 0 {
+                    eprintln!("ERROR: {}", e);
+                }
+                return;
+            }
+        };
 
-    // Override num_tasks from MPI if not set (ref: ior.c:904-935)
-    if params.num_tasks == -1 {
-        params.num_tasks = mpi_size;
-    } else if params.num_tasks > mpi_size {
-        if rank == 0 {
-            eprintln!(
-                "WARNING: requested {} tasks but only {} available, using {}",
-                params.num_tasks, mpi_size, mpi_size
-            );
+        // Script mode (-f): one test per RUN, inheriting the command-line settings
+        let script = args.script.clone();
+        let explicit_access = (args.write_file, args.read_file);
+        let cli_params = match args.into_ior_param() {
+            Ok(params) => params,
+            Err(errors) => {
+                if rank == 0 {
+                    for e in errors {
+                        eprintln!("ERROR: {}", e);
+                    }
+                }
+                return;
+            }
+        };
+        let params = match script {
+            Some(path) => {
+                let mut base = cli_params;
+                (base.write_file, base.read_file) = explicit_access;
+                let parsed = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("cannot read script {}: {}", path, e))
+                    .and_then(|text| script::parse_script(&text, &base));
+                match parsed {
+                    Ok(tests) => tests,
+                    Err(e) => {
+                        if rank == 0 {
+                            eprintln!("ERROR: {}", e);
+                        }
+                        return;
+                    }
+                }
+            }
+            None => vec![cli_params],
+        };
+        for p in &params {
+            for (point, labels) in sweep::expand(p, &axes) {
+                tests.push((point, labels, backend_options.clone()));
+            }
         }
-        params.num_tasks = mpi_size;
     }
+    let multiple_tests = tests.len() > 1;
 
-    // Print test configuration (rank 0 only)
-    if rank == 0 && print_text {
-        println!("IOR-bench (Rust async-ior)");
-        println!(
-            "  api            = {}",
-            params.api_str()
-        );
-        println!("  num_tasks      = {}", params.num_tasks);
-        println!("  block_size     = {}", params.block_size);
-        println!("  transfer_size  = {}", params.transfer_size);
-        println!("  segment_count  = {}", params.segment_count);
-        println!("  repetitions    = {}", params.repetitions);
-        println!(
-            "  test_file      = {}",
-            params.test_file_name_str()
-        );
-        println!("  file_per_proc  = {}", params.file_per_proc);
-        println!("  direct_io      = {}", params.direct_io);
-        println!("  queue_depth    = {}", params.queue_depth);
-
-        // Print backend-specific options
-        let prefix = params.api_str().to_lowercase();
-        for (key, value) in backend_options.for_prefix(&prefix) {
-            match value {
-                ior_core::OptionValue::Flag => {
-                    println!("  {}.{} = true", prefix, key);
-                }
-                ior_core::OptionValue::Str(s) => {
-                    println!("  {}.{} = {}", prefix, key, s);
+    let test_options = tests.iter().map(|(params, _, options)| (params, options));
+    if !validate_on_root(test_options, multiple_tests, world) {
+        return;
+    }
+
+    let nodes = topology::NodeMap::detect(world);
+    let mut runs = Vec::new();
+    let mut sweep_points = Vec::new();
+    let mut context: Option<TestContext<C>> = None;
+    for (test_id, (mut params, labels, backend_options)) in tests.into_iter().enumerate() {
+        // Override num_tasks from the communicator if not set (ref: ior.c:904-935)
+        if params.num_tasks == -1 {
+            params.num_tasks = world_size;
+        } else if params.num_tasks > world_size {
+            if rank == 0 {
+                eprintln!(
+                    "WARNING: requested {} tasks but only {} available, using {}",
+                    params.num_tasks, world_size, world_size
+                );
+            }
+            params.num_tasks = world_size;
+        }
+        params.num_nodes = nodes.num_nodes(params.num_tasks);
+        params.num_tasks_on_node0 = nodes.tasks_on_node0(params.num_tasks);
+        if rank == 0 && params.reorder_tasks && params.num_nodes == 1 {
+            eprintln!("WARNING: all tasks run on one node, -C cannot avoid local reads");
+        }
+
+        // Print test configuration (rank 0 only)
+        if rank == 0 && print_text {
+            if multiple_tests {
+                println!();
+                println!("Test {}:", test_id);
+            }
+            print_config(&params, &backend_options);
+        }
+
+        let start_time = json_output::current_time_string();
+        if let Some(results) =
+            run_test(&params, &backend_options, world, &mut context, print_text)
+        {
+            if !labels.is_empty() {
+                sweep_points.push((test_id, labels, runs.len()));
+            }
+            runs.push(json_output::TestRun {
+                params,
+                results,
+                start_time,
+            });
+        }
+    }
+
+    // Sweep table (rank 0 only)
+    if rank == 0 && print_text && !sweep_points.is_empty() {
+        let rows: Vec<_> = sweep_points
+            .iter()
+            .map(|(test_id, point, run)| report::SweepRow {
+                test_id: *test_id,
+                point,
+                results: &runs[*run].results,
+            })
+            .collect();
+        report::print_sweep_summary(&rows);
+    }
+
+    // JSON output (rank 0 only)
+    if rank == 0 && json_mode && !runs.is_empty() {
+        let doc = json_output::build_ior_json(&runs, &command_line);
+        let json_str = serde_json::to_string_pretty(&doc)
+            .expect("failed to serialize JSON");
+
+        if json_stdout {
+            println!("{}", json_str);
+        }
+
+        if let Some(ref path) = json_file {
+            std::fs::write(path, &json_str)
+                .unwrap_or_else(|e| eprintln!("ERROR: failed to write JSON file: {}", e));
+        }
+    }
+
+    // Synchronize all ranks before exit
+    world.barrier();
+}
+
+/// Validate every test on rank 0, print all problems, and broadcast the
+/// verdict so that every rank either runs or exits before any collective.
+fn validate_on_root<'a>(
+    tests: impl Iterator<Item = (&'a ior_core::IorParam, &'a ior_core::BackendOptions)>,
+    multiple_tests: bool,
+    world: &impl Comm,
+) -> bool {
+    let mut valid = 1i32;
+    if world.rank() == 0 {
+        for (test_id, (params, backend_options)) in tests.enumerate() {
+            let conflict = collective_conflict(params, backend_options);
+            for e in params.validate().into_iter().chain(conflict) {
+                if multiple_tests {
+                    eprintln!("ERROR: test {}: {}", test_id, e);
+                } else {
+                    eprintln!("ERROR: {}", e);
                 }
+                valid = 0;
             }
         }
     }
+    world.broadcast_value(0, &mut valid);
+    valid != 0
+}
 
-    // Create test subcommunicator for first num_tasks ranks (ref: ior.c:124-171)
-    let color = if rank < params.num_tasks {
-        Color::with_value(0)
+/// Reject collective MPIIO on a shared file when ranks may stop after
+/// different numbers of transfers: every rank must issue the same sequence of
+/// `_all` calls, but `-D`, `-T` and the min-time loop end each rank on its own
+/// clock, and `-z` gives each rank its own offset count.
+fn collective_conflict(
+    params: &ior_core::IorParam,
+    backend_options: &ior_core::BackendOptions,
+) -> Option<ior_core::ParamError> {
+    let collective = backend_options
+        .get("mpiio.collective")
+        .is_some_and(|v| v.as_bool());
+    if !collective || params.api_str() != "MPIIO" || params.file_per_proc {
+        return None;
+    }
+    let option = if params.deadline_for_stonewalling > 0 {
+        "stonewalling (-D)"
+    } else if params.max_time_duration > 0 {
+        "max time duration (-T)"
+    } else if params.min_time_duration > 0 {
+        "min time duration"
+    } else if params.random_offset {
+        "random offsets (-z)"
     } else {
-        Color::undefined()
+        return None;
     };
-    let test_comm = world.split_by_color(color);
+    Some(ior_core::ParamError::Conflict(format!(
+        "collective MPIIO (--mpiio.collective) on a shared file cannot be combined with {}",
+        option
+    )))
+}
 
-    if rank >= params.num_tasks {
-        // Non-participating rank
-        world.barrier();
-        return;
+/// Print the test configuration header.
+fn print_config(params: &ior_core::IorParam, backend_options: &ior_core::BackendOptions) {
+    println!("IOR-bench (Rust async-ior)");
+    println!(
+        "  api            = {}",
+        params.api_str()
+    );
+    println!("  num_tasks      = {}", params.num_tasks);
+    println!("  num_nodes      = {}", params.num_nodes);
+    println!("  tasks_per_node = {}", params.num_tasks_on_node0);
+    println!("  block_size     = {}", params.block_size);
+    println!("  transfer_size  = {}", params.transfer_size);
+    println!("  segment_count  = {}", params.segment_count);
+    println!("  repetitions    = {}", params.repetitions);
+    println!(
+        "  test_file      = {}",
+        params.test_file_name_str()
+    );
+    println!("  file_per_proc  = {}", params.file_per_proc);
+    if !params.file_per_proc {
+        println!("  layout         = {}", params.layout.as_str());
     }
+    println!("  direct_io      = {}", params.direct_io);
+    println!("  queue_depth    = {}", params.queue_depth);
 
-    let test_comm = test_comm.expect("failed to create test communicator");
+    // Print backend-specific options
+    let prefix = params.api_str().to_lowercase();
+    for (key, value) in backend_options.for_prefix(&prefix) {
+        match value {
+            ior_core::OptionValue::Flag => {
+                println!("  {}.{} = true", prefix, key);
+            }
+            ior_core::OptionValue::Str(s) => {
+                println!("  {}.{} = {}", prefix, key, s);
+            }
+        }
+    }
+}
 
-    // Select backend and configure backend-specific options
-    let mut backend = select_backend(&params);
-    if let Err(e) = backend.as_mut().configure(&backend_options) {
-        eprintln!("ERROR: invalid backend option: {}", e);
-        world.barrier();
-        return;
+/// What a test needs from its backend and communicator; consecutive tests
+/// with the same key share both.
+#[derive(PartialEq)]
+struct BackendKey {
+    api: String,
+    num_tasks: i32,
+    direct_io: bool,
+    queue_depth: i32,
+    file_per_proc: bool,
+    options: ior_core::BackendOptions,
+}
+
+impl BackendKey {
+    fn new(params: &ior_core::IorParam, backend_options: &ior_core::BackendOptions) -> Self {
+        Self {
+            api: params.api_str().to_string(),
+            num_tasks: params.num_tasks,
+            direct_io: params.direct_io,
+            queue_depth: params.queue_depth,
+            file_per_proc: params.file_per_proc,
+            options: backend_options.clone(),
+        }
     }
+}
+
+/// Backend and test communicator kept across tests. `env` is None on ranks
+/// outside the test communicator; a backend whose options were rejected is
+/// kept as None so every rank still agrees on the key.
+struct TestContext<C> {
+    key: BackendKey,
+    // The backend comes first so it is dropped before the communicator
+    // (MPIIO holds the raw communicator)
+    env: Option<(Option<Box<dyn ior_core::Aiori>>, C)>,
+}
+
+/// Run one test on the first `num_tasks` ranks, reusing the backend and
+/// communicator of the previous test when its `BackendKey` matches. Returns
+/// None on ranks that do not take part and when the test fails.
+fn run_test<C: Comm>(
+    params: &ior_core::IorParam,
+    backend_options: &ior_core::BackendOptions,
+    world: &C,
+    context: &mut Option<TestContext<C>>,
+    print_text: bool,
+) -> Option<runner::BenchmarkResults> {
+    let rank = world.rank();
+
+    let key = BackendKey::new(params, backend_options);
+    if context.as_ref().is_none_or(|c| c.key != key) {
+        // Release the previous backend before creating a new one
+        *context = None;
+
+        // Create test subcommunicator for first num_tasks ranks (ref: ior.c:124-171)
+        let color = (rank < params.num_tasks).then_some(0);
+        let env = world.split(color, 0).map(|test_comm| {
+            // Select backend and configure backend-specific options
+            let backend = match select_backend(params, &test_comm) {
+                Ok(backend) => {
+                    let mut backend = ior_core::with_faults(backend, backend_options);
+                    match backend.as_mut().configure(backend_options) {
+                        Ok(()) => Some(backend),
+                        Err(e) => {
+                            eprintln!("ERROR: invalid backend option: {}", e);
+                            None
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("ERROR: {}", e);
+                    None
+                }
+            };
+            (backend, test_comm)
+        });
+        *context = Some(TestContext { key, env });
+    }
+
+    // Non-participating rank, or backend options were rejected
+    let (Some(backend), test_comm) = context.as_ref()?.env.as_ref()? else {
+        return None;
+    };
 
     // Run the benchmark: async path for queue_depth > 1, sync path otherwise
     let result = if params.queue_depth > 1 {
-        runner::run_benchmark_async(&params, backend.as_ref(), &test_comm, print_text)
+        runner::run_benchmark_async(params, backend.as_ref(), test_comm, print_text)
     } else {
-        runner::run_benchmark(&params, backend.as_ref(), &test_comm, print_text)
+        runner::run_benchmark(params, backend.as_ref(), test_comm, print_text)
     };
 
     match result {
-        Ok(bench_results) => {
-            // JSON output (rank 0 only)
-            if rank == 0 && json_mode {
-                let doc = json_output::build_ior_json(&params, &bench_results, &command_line);
-                let json_str = serde_json::to_string_pretty(&doc)
-                    .expect("failed to serialize JSON");
-
-                if json_stdout {
-                    println!("{}", json_str);
-                }
-
-                if let Some(ref path) = json_file {
-                    std::fs::write(path, &json_str)
-                        .unwrap_or_else(|e| eprintln!("ERROR: failed to write JSON file: {}", e));
-                }
-            }
-        }
+        Ok(bench_results) => Some(bench_results),
         Err(e) => {
+            // The other ranks would wait for this one forever (ref: ior.c ERR -> MPI_Abort)
             eprintln!("ERROR [rank {}]: {}", rank, e);
+            test_comm.abort(1)
         }
     }
-
-    // Synchronize all ranks before exit
-    world.barrier();
-    // MPI_Finalize happens on drop of `universe`
 }
 
 /// Select I/O backend based on API name.
-fn select_backend(params: &ior_core::IorParam) -> Box<dyn ior_core::Aiori> {
+///
+/// `comm` is the test communicator, used by MPIIO to open shared files.
+#[cfg_attr(not(feature = "mpi"), allow(unused_variables))]
+fn select_backend(
+    params: &ior_core::IorParam,
+    comm: &impl Comm,
+) -> Result<Box<dyn ior_core::Aiori>, String> {
     let direct_io = params.direct_io;
     let queue_depth = params.queue_depth;
 
-    match params.api_str() {
+    let backend: Box<dyn ior_core::Aiori> = match params.api_str() {
         "POSIX" => {
             if queue_depth > 1 {
                 // Create with thread pool for async I/O
@@ -155,9 +439,21 @@ fn select_backend(params: &ior_core::IorParam) -> Box<dyn ior_core::Aiori> {
         }
         "BENCHFS" => Box::new(ior_backend_benchfs::BenchfsBackend::new()),
         "CHFS" => Box::new(ior_backend_chfs::ChfsBackend::new()),
+        "MEMORY" => Box::new(ior_backend_memory::MemoryBackend::new()),
+        #[cfg(feature = "mpi")]
+        "MPIIO" => match comm.as_mpi() {
+            Some(mpi_comm) => Box::new(ior_backend_mpiio::MpiioBackend::new(
+                mpi_comm,
+                params.file_per_proc,
+            )),
+            None => return Err("MPIIO needs MPI ranks and cannot run with --threads".into()),
+        },
+        #[cfg(not(feature = "mpi"))]
+        "MPIIO" => return Err("MPIIO is not available without the mpi feature".into()),
         other => {
             eprintln!("Unknown API: {}, falling back to POSIX", other);
             Box::new(ior_backend_posix::PosixBackend::new(direct_io))
         }
-    }
+    };
+    Ok(backend)
 }
@@ -1,7 +1,10 @@
+use ior_core::histogram::{LatencyHistogram, HISTOGRAM_BUCKETS};
 use ior_core::timer::{BenchTimers, IOR_NB_TIMERS};
-use mpi::collective::SystemOperation;
-use mpi::topology::SimpleCommunicator;
-use mpi::traits::*;
+use ior_core::{Comm, ReduceOp};
+
+use crate::runner::BenchmarkResults;
+use crate::sampler::BandwidthSampler;
+use crate::verify::MismatchReport;
 
 const MEBIBYTE: f64 = 1_048_576.0;
 const KIBIBYTE: f64 = 1024.0;
@@ -27,6 +30,101 @@ pub struct IterResult {
     pub data_moved: i64,
     /// Repetition number
     pub rep: i32,
+    /// Stonewall statistics (None when stonewalling is disabled)
+    pub stonewall: Option<StonewallResult>,
+    /// The phase hit max_time_duration on at least one rank
+    pub truncated: bool,
+    /// Per-transfer latency percentiles merged across ranks
+    pub latency_hist: LatencyPercentiles,
+    /// Aggregate bandwidth samples (None unless `--sample-interval` is set)
+    pub time_series: Option<TimeSeries>,
+    /// Data verification errors summed over all ranks (None unless the phase
+    /// was checked with `-W`/`-R`)
+    pub verify_errors: Option<u64>,
+    /// First mismatching words of each rank (empty if verification passed)
+    pub mismatches: Vec<MismatchReport>,
+}
+
+/// Bytes completed by all ranks in each sample interval of a phase.
+#[derive(Debug, Clone)]
+pub struct TimeSeries {
+    /// Interval length in seconds
+    pub interval: f64,
+    /// Aggregate bytes per interval; the last interval may be partial
+    pub bytes: Vec<i64>,
+}
+
+/// Per-transfer latency percentiles in seconds.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyPercentiles {
+    /// Number of transfers sampled
+    pub samples: u64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub p999: f64,
+    pub max: f64,
+}
+
+impl LatencyPercentiles {
+    pub fn from_histogram(hist: &LatencyHistogram) -> Self {
+        Self {
+            samples: hist.count(),
+            p50: hist.percentile_secs(50.0),
+            p90: hist.percentile_secs(90.0),
+            p99: hist.percentile_secs(99.0),
+            p999: hist.percentile_secs(99.9),
+            max: hist.percentile_secs(100.0),
+        }
+    }
+}
+
+/// Per-rank state at the moment the stonewalled loop ended.
+#[derive(Debug, Clone)]
+pub struct StonewallPoint {
+    /// Seconds from the start of the transfer loop
+    pub time: f64,
+    /// Transfers (pairs) completed
+    pub pairs: u64,
+    /// Bytes moved
+    pub data_moved: i64,
+}
+
+/// Stonewall statistics reduced across ranks.
+#[derive(Debug, Clone)]
+pub struct StonewallResult {
+    /// Slowest rank's time to reach the stonewall
+    pub time: f64,
+    pub pairs_min: u64,
+    pub pairs_max: u64,
+    /// Aggregate data moved before the stonewall
+    pub data_moved: i64,
+    /// Bandwidth up to the stonewall in bytes/sec
+    pub bw: f64,
+}
+
+/// Reduce stonewall points across ranks using MPI_Allreduce.
+///
+/// Reference: `ior.c:1866-1882`
+pub fn reduce_stonewall(point: &StonewallPoint, comm: &impl Comm) -> StonewallResult {
+    let time = comm.all_reduce_value(point.time, ReduceOp::Max);
+    let pairs_min = comm.all_reduce_value(point.pairs, ReduceOp::Min);
+    let pairs_max = comm.all_reduce_value(point.pairs, ReduceOp::Max);
+    let data_moved = reduce_data_moved(point.data_moved, comm);
+
+    let bw = if time > 0.0 {
+        data_moved as f64 / time
+    } else {
+        0.0
+    };
+
+    StonewallResult {
+        time,
+        pairs_min,
+        pairs_max,
+        data_moved,
+        bw,
+    }
 }
 
 /// Reduce timers across MPI ranks.
@@ -35,26 +133,15 @@ pub struct IterResult {
 /// Only rank 0 gets meaningful reduced values.
 ///
 /// Reference: `ior.c:804-808`
-pub fn reduce_timers(timers: &BenchTimers, comm: &SimpleCommunicator) -> BenchTimers {
-    let rank = comm.rank();
-    let root = comm.process_at_rank(0);
-
+pub fn reduce_timers(timers: &BenchTimers, comm: &impl Comm) -> BenchTimers {
     let mut reduced = BenchTimers::default();
 
     for i in 0..IOR_NB_TIMERS {
         let val = timers.timers[i];
-        if i % 2 == 0 {
-            if rank == 0 {
-                root.reduce_into_root(&val, &mut reduced.timers[i], SystemOperation::min());
-            } else {
-                root.reduce_into(&val, SystemOperation::min());
-            }
-        } else {
-            if rank == 0 {
-                root.reduce_into_root(&val, &mut reduced.timers[i], SystemOperation::max());
-            } else {
-                root.reduce_into(&val, SystemOperation::max());
-            }
+        let op = if i % 2 == 0 { ReduceOp::Min } else { ReduceOp::Max };
+        let value = comm.reduce_value(0, val, op);
+        if comm.rank() == 0 {
+            reduced.timers[i] = value;
         }
     }
 
@@ -62,10 +149,48 @@ pub fn reduce_timers(timers: &BenchTimers, comm: &SimpleCommunicator) -> BenchTi
 }
 
 /// Aggregate data moved across all ranks using MPI_Allreduce(SUM).
-pub fn reduce_data_moved(local_data_moved: i64, comm: &SimpleCommunicator) -> i64 {
-    let mut agg: i64 = 0;
-    comm.all_reduce_into(&local_data_moved, &mut agg, SystemOperation::sum());
-    agg
+pub fn reduce_data_moved(local_data_moved: i64, comm: &impl Comm) -> i64 {
+    comm.all_reduce_value(local_data_moved, ReduceOp::Sum)
+}
+
+/// True on every rank if `flag` is set on any rank (MPI_Allreduce(MAX)).
+pub fn reduce_any(flag: bool, comm: &impl Comm) -> bool {
+    comm.all_reduce_value(flag as i32, ReduceOp::Max) != 0
+}
+
+/// Merge per-rank latency histograms on rank 0 (MPI_Reduce of the bucket
+/// counts) and extract percentiles. Only rank 0 gets meaningful values.
+pub fn reduce_latency(hist: &LatencyHistogram, comm: &impl Comm) -> LatencyPercentiles {
+    let mut counts = vec![0u64; HISTOGRAM_BUCKETS];
+    comm.reduce(0, hist.counts(), &mut counts[..], ReduceOp::Sum);
+    let min = comm.reduce_value(0, hist.min(), ReduceOp::Min);
+    let max = comm.reduce_value(0, hist.max(), ReduceOp::Max);
+    if comm.rank() != 0 {
+        return LatencyPercentiles::default();
+    }
+
+    LatencyPercentiles::from_histogram(&LatencyHistogram::from_parts(counts, min, max))
+}
+
+/// Sum per-rank bandwidth samples interval by interval on rank 0. Ranks with
+/// shorter series are padded with zeros. Only rank 0 gets meaningful values.
+pub fn reduce_time_series(
+    sampler: Option<&BandwidthSampler>,
+    interval: f64,
+    comm: &impl Comm,
+) -> TimeSeries {
+    let local = sampler.map_or(&[][..], |s| s.bytes());
+    let len = comm.all_reduce_value(local.len() as u64, ReduceOp::Max);
+
+    let mut padded = local.to_vec();
+    padded.resize(len as usize, 0);
+
+    let mut bytes = vec![0i64; len as usize];
+    if len > 0 {
+        comm.reduce(0, &padded, &mut bytes, ReduceOp::Sum);
+    }
+
+    TimeSeries { interval, bytes }
 }
 
 /// Compute performance metrics from reduced timers and aggregate data.
@@ -77,12 +202,9 @@ pub fn compute_metrics(
     agg_data: i64,
     transfer_size: i64,
     block_size: i64,
-    comm: &SimpleCommunicator,
+    comm: &impl Comm,
     rep: i32,
 ) -> IterResult {
-    let rank = comm.rank();
-    let root = comm.process_at_rank(0);
-
     let total_time = reduced.total_time();
     let access_time = reduced.rdwr_time();
     let open_time = reduced.open_time();
@@ -112,12 +234,7 @@ pub fn compute_metrics(
         0.0
     };
 
-    let mut min_latency = 0.0f64;
-    if rank == 0 {
-        root.reduce_into_root(&local_latency, &mut min_latency, SystemOperation::min());
-    } else {
-        root.reduce_into(&local_latency, SystemOperation::min());
-    }
+    let min_latency = comm.reduce_value(0, local_latency, ReduceOp::Min);
 
     IterResult {
         bw,
@@ -129,13 +246,19 @@ pub fn compute_metrics(
         total_time,
         data_moved: agg_data,
         rep,
+        stonewall: None,
+        truncated: false,
+        latency_hist: LatencyPercentiles::default(),
+        time_series: None,
+        verify_errors: None,
+        mismatches: Vec::new(),
     }
 }
 
 /// Print table header (rank 0 only).
 ///
 /// Reference: `ior-output.c:21`
-pub fn print_header(comm: &SimpleCommunicator) {
+pub fn print_header(comm: &impl Comm) {
     if comm.rank() != 0 {
         return;
     }
@@ -178,7 +301,7 @@ pub fn print_result(
     result: &IterResult,
     block_size: i64,
     transfer_size: i64,
-    comm: &SimpleCommunicator,
+    comm: &impl Comm,
 ) {
     if comm.rank() != 0 {
         return;
@@ -197,6 +320,30 @@ pub fn print_result(
         result.total_time,
         result.rep,
     );
+
+    // Reference: `ior.c:1878` (stonewalling pairs accessed)
+    if let Some(ref sw) = result.stonewall {
+        println!(
+            "stonewalling pairs accessed min: {} max: {} -- data: {:.2} MiB time: {:.3}s bw: {:.2} MiB/s",
+            sw.pairs_min,
+            sw.pairs_max,
+            sw.data_moved as f64 / MEBIBYTE,
+            sw.time,
+            sw.bw / MEBIBYTE,
+        );
+    }
+
+    let lat = &result.latency_hist;
+    if lat.samples > 0 {
+        println!(
+            "{:<10} latency(s) p50: {:.6} p90: {:.6} p99: {:.6} p99.9: {:.6} max: {:.6}",
+            "", lat.p50, lat.p90, lat.p99, lat.p999, lat.max,
+        );
+    }
+
+    if result.truncated {
+        println!("WARNING: {} phase truncated by max_time_duration", access);
+    }
 }
 
 /// Summary statistics for multiple repetitions.
@@ -240,7 +387,7 @@ pub fn print_summary(
     results: &[IterResult],
     _block_size: i64,
     _transfer_size: i64,
-    comm: &SimpleCommunicator,
+    comm: &impl Comm,
 ) {
     if comm.rank() != 0 || results.is_empty() {
         return;
@@ -311,3 +458,77 @@ pub fn print_summary(
         data_stats.mean
     );
 }
+
+/// One finished sweep point.
+pub struct SweepRow<'a> {
+    pub test_id: usize,
+    /// Swept `(key, value)` pairs of this point
+    pub point: &'a [(String, String)],
+    pub results: &'a BenchmarkResults,
+}
+
+/// Print one line per sweep point with the mean bandwidth and IOPS of each
+/// phase over its repetitions (rank 0 only).
+pub fn print_sweep_summary(rows: &[SweepRow]) {
+    // One column per swept key, in order of first appearance
+    let mut keys: Vec<&str> = Vec::new();
+    for row in rows {
+        for (key, _) in row.point {
+            if !keys.contains(&key.as_str()) {
+                keys.push(key);
+            }
+        }
+    }
+    let widths: Vec<usize> = keys
+        .iter()
+        .map(|key| {
+            rows.iter()
+                .flat_map(|row| row.point)
+                .filter(|(k, _)| k == key)
+                .map(|(_, v)| v.len())
+                .chain([key.len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mean = |results: &[IterResult], f: fn(&IterResult) -> f64| -> String {
+        if results.is_empty() {
+            return "-".to_string();
+        }
+        let values: Vec<f64> = results.iter().map(f).collect();
+        format!("{:.2}", SummaryStats::from_values(&values).mean)
+    };
+
+    println!();
+    println!("Sweep summary:");
+    let mut header = format!("{:<6}", "Test");
+    for (key, width) in keys.iter().zip(&widths) {
+        header += &format!(" {:<width$}", key, width = width);
+    }
+    header += &format!(
+        " {:>12} {:>12} {:>12} {:>12}",
+        "write(MiB)", "write(OPs)", "read(MiB)", "read(OPs)"
+    );
+    println!("{}", header);
+
+    for row in rows {
+        let mut line = format!("{:<6}", row.test_id);
+        for (key, width) in keys.iter().zip(&widths) {
+            let value = row
+                .point
+                .iter()
+                .find(|(k, _)| k == key)
+                .map_or("-", |(_, v)| v.as_str());
+            line += &format!(" {:<width$}", value, width = width);
+        }
+        line += &format!(
+            " {:>12} {:>12} {:>12} {:>12}",
+            mean(&row.results.write_results, |r| r.bw / MEBIBYTE),
+            mean(&row.results.write_results, |r| r.iops),
+            mean(&row.results.read_results, |r| r.bw / MEBIBYTE),
+            mean(&row.results.read_results, |r| r.iops),
+        );
+        println!("{}", line);
+    }
+}
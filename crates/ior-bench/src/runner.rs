@@ -1,16 +1,15 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 
 use ior_core::error::IorError;
-use ior_core::handle::{OpenFlags, XferDir, XferResult};
-use ior_core::params::IorParam;
+use ior_core::handle::{OpenFlags, XferDir, XferResult, XferToken};
+use ior_core::params::{AccessLayout, IorParam};
 use ior_core::timer::BenchTimers;
 use ior_core::data_pattern;
-use ior_core::{now, AlignedBuffer, Aiori};
-use mpi::collective::SystemOperation;
-use mpi::topology::SimpleCommunicator;
-use mpi::traits::*;
+use ior_core::{now, AlignedBuffer, Aiori, Comm, LatencyHistogram, ReduceOp};
 
 use crate::report;
+use crate::sampler::BandwidthSampler;
+use crate::verify::{self, VerifyLog};
 
 /// Results from a complete benchmark run (all iterations).
 pub struct BenchmarkResults {
@@ -24,7 +23,7 @@ pub struct BenchmarkResults {
 pub fn run_benchmark(
     params: &IorParam,
     backend: &dyn Aiori,
-    comm: &SimpleCommunicator,
+    comm: &impl Comm,
     print_text: bool,
 ) -> Result<BenchmarkResults, IorError> {
     let rank = comm.rank();
@@ -39,6 +38,8 @@ pub fn run_benchmark(
 
     for rep in 0..params.repetitions {
         let mut rank_offset: i32 = 0;
+        // Pair count reached by the write's stonewall wear-out; caps the read
+        let mut wear_out_pairs: Option<u64> = None;
 
         // === WRITE PHASE === (ref: ior.c:1287-1340)
         if params.write_file {
@@ -71,8 +72,18 @@ pub fn run_benchmark(
             }
 
             timers.timers[2] = now();
-            let (data_moved, _) =
-                write_or_read(&handle, XferDir::Write, params, backend, rank, rank_offset, comm)?;
+            let outcome = write_or_read(
+                &handle,
+                XferDir::Write,
+                params,
+                backend,
+                rank,
+                rank_offset,
+                pair_limit(params, None),
+                comm,
+            )?;
+            let data_moved = outcome.data_moved;
+            wear_out_pairs = outcome.wear_out_pairs;
             timers.timers[3] = now();
 
             if params.intra_test_barriers {
@@ -90,25 +101,32 @@ pub fn run_benchmark(
             comm.barrier(); // ior.c:1328
             check_file_size(params, backend, data_moved, rank, rank_offset, comm);
 
-            let result =
-                reduce_and_report("write", &timers, params, data_moved, comm, rep, print_text);
-            if let Some(r) = result {
-                write_results.push(r);
-            }
+            let mut result = reduce_and_report(
+                "write",
+                &timers,
+                params,
+                &outcome,
+                comm,
+                rep,
+                print_text,
+            );
 
             // === WRITECHECK PHASE === (ref: ior.c:1346-1369)
             if params.check_write {
                 comm.barrier();
-                let errors = write_or_read_verify(params, backend, rank, rank_offset, comm)?;
-                let mut total_errors: usize = 0;
-                comm.all_reduce_into(&errors, &mut total_errors, SystemOperation::sum());
-                if rank == 0 && total_errors > 0 {
-                    eprintln!("WARNING: WRITECHECK found {} data errors", total_errors);
-                } else if rank == 0 && params.verbose > 0 {
-                    eprintln!("INFO: WRITECHECK passed (0 errors)");
+                let log =
+                    write_or_read_verify(params, backend, rank, rank_offset, outcome.pairs, comm)?;
+                let (total, mismatches) =
+                    reduce_check_errors("WRITECHECK", &log, params, rank, rank_offset, comm);
+                if let Some(r) = result.as_mut() {
+                    r.verify_errors = Some(total);
+                    r.mismatches = mismatches;
                 }
                 comm.barrier();
             }
+            if let Some(r) = result {
+                write_results.push(r);
+            }
         }
 
         // === READ PHASE === (ref: ior.c:1373-1459)
@@ -122,7 +140,7 @@ pub fn run_benchmark(
 
             // Task reordering for read-back (ref: ior.c:1389-1421)
             if params.reorder_tasks {
-                rank_offset = params.task_per_node_offset % num_tasks;
+                rank_offset = reorder_offset(params);
             } else if params.reorder_tasks_random {
                 rank_offset = random_rank_offset(rank, num_tasks, params.reorder_tasks_random_seed);
             }
@@ -145,8 +163,16 @@ pub fn run_benchmark(
             }
 
             timers.timers[2] = now();
-            let (data_moved, read_errors) =
-                write_or_read(&handle, XferDir::Read, params, backend, rank, rank_offset, comm)?;
+            let outcome = write_or_read(
+                &handle,
+                XferDir::Read,
+                params,
+                backend,
+                rank,
+                rank_offset,
+                pair_limit(params, wear_out_pairs),
+                comm,
+            )?;
             timers.timers[3] = now();
 
             if params.intra_test_barriers {
@@ -157,19 +183,18 @@ pub fn run_benchmark(
             backend.close(handle)?;
             timers.timers[5] = now();
 
+            let mut result =
+                reduce_and_report("read", &timers, params, &outcome, comm, rep, print_text);
+
             // READCHECK result reporting
             if params.check_read {
-                let mut total_errors: usize = 0;
-                comm.all_reduce_into(&read_errors, &mut total_errors, SystemOperation::sum());
-                if rank == 0 && total_errors > 0 {
-                    eprintln!("WARNING: READCHECK found {} data errors", total_errors);
-                } else if rank == 0 && params.verbose > 0 {
-                    eprintln!("INFO: READCHECK passed (0 errors)");
+                let (total, mismatches) =
+                    reduce_check_errors("READCHECK", &outcome.verify, params, rank, rank_offset, comm);
+                if let Some(r) = result.as_mut() {
+                    r.verify_errors = Some(total);
+                    r.mismatches = mismatches;
                 }
             }
-
-            let result =
-                reduce_and_report("read", &timers, params, data_moved, comm, rep, print_text);
             if let Some(r) = result {
                 read_results.push(r);
             }
@@ -198,9 +223,167 @@ pub fn run_benchmark(
     Ok(BenchmarkResults { write_results, read_results })
 }
 
+/// Outcome of one write or read phase on this rank.
+struct PhaseOutcome {
+    data_moved: i64,
+    /// Data verification errors (READCHECK only).
+    verify: VerifyLog,
+    /// Transfers issued on this rank, including the wear-out.
+    pairs: u64,
+    /// State at the moment the timed loop ended (stonewalling enabled only).
+    stonewall: Option<report::StonewallPoint>,
+    /// Pair count every rank continued to after the stonewall (wear-out only).
+    wear_out_pairs: Option<u64>,
+    /// The phase was aborted by max_time_duration.
+    timed_out: bool,
+    /// Per-transfer latency on this rank.
+    latency: LatencyHistogram,
+    /// Bytes completed per sample interval (`--sample-interval` only).
+    samples: Option<BandwidthSampler>,
+}
+
+/// Pair limit for a phase: the explicit wear-out iteration count if set,
+/// otherwise the count carried over from the preceding write's wear-out.
+fn pair_limit(params: &IorParam, carried: Option<u64>) -> u64 {
+    if params.stonewall_wear_out_iterations > 0 {
+        params.stonewall_wear_out_iterations
+    } else {
+        carried.unwrap_or(u64::MAX)
+    }
+}
+
+/// Hard per-phase time limit in seconds from `-T` (given in minutes).
+fn phase_time_limit(params: &IorParam) -> Option<f64> {
+    (params.max_time_duration > 0).then_some(params.max_time_duration as f64 * 60.0)
+}
+
+/// Bandwidth sampler starting now, if `--sample-interval` is set.
+fn new_sampler(params: &IorParam) -> Option<BandwidthSampler> {
+    (params.sample_interval > 0.0).then(|| BandwidthSampler::new(params.sample_interval, now()))
+}
+
+/// Whether the stonewall wear-out is active for this run.
+fn wear_out_enabled(params: &IorParam) -> bool {
+    params.deadline_for_stonewalling > 0 && params.stonewall_wear_out
+}
+
+/// File offset of this rank's `idx`-th transfer (segment-major order).
+///
+/// Reference: `ior.c:1823-1829`
+fn transfer_offset(
+    params: &IorParam,
+    pretend_rank: i32,
+    random_offsets: Option<&[i64]>,
+    idx: u64,
+) -> i64 {
+    let num_tasks = params.num_tasks as i64;
+    if let Some(offsets) = random_offsets {
+        let num_per_seg = offsets.len() as u64;
+        let seg = (idx / num_per_seg) as i64;
+        let base = offsets[(idx % num_per_seg) as usize];
+        if params.file_per_proc {
+            base + seg * params.block_size
+        } else {
+            base + seg * num_tasks * params.block_size
+        }
+    } else {
+        let offsets_per_block = (params.block_size / params.transfer_size) as u64;
+        let seg = (idx / offsets_per_block) as i64;
+        let j = (idx % offsets_per_block) as i64;
+        if params.file_per_proc {
+            j * params.transfer_size + seg * params.block_size
+        } else {
+            seg * num_tasks * params.block_size + shared_slot_offset(params, pretend_rank, j)
+        }
+    }
+}
+
+/// Offset of `rank`'s `j`-th transfer within one segment of a shared file.
+fn shared_slot_offset(params: &IorParam, rank: i32, j: i64) -> i64 {
+    match params.layout {
+        // Interleaved blocks per rank
+        AccessLayout::Segmented => rank as i64 * params.block_size + j * params.transfer_size,
+        // Interleaved transfers per rank
+        AccessLayout::Strided => (j * params.num_tasks as i64 + rank as i64) * params.transfer_size,
+    }
+}
+
+/// Upper bound on resubmissions of one incomplete transfer.
+const MAX_RETRY: u32 = 10_000;
+
+/// Synchronous transfer that resubmits the remainder of a short transfer.
+///
+/// With `single_xfer_attempt` a short transfer is an error instead. A
+/// transfer that moves nothing (end of file on read) is not retried.
+/// Collective backends report short transfers as errors themselves, since
+/// only the short rank would join a retry.
+///
+/// Reference: `aiori-POSIX.c:POSIX_Xfer` (retry loop)
+fn xfer_full(
+    backend: &dyn Aiori,
+    handle: &ior_core::FileHandle,
+    access: XferDir,
+    buf: *mut u8,
+    params: &IorParam,
+    offset: i64,
+) -> Result<i64, IorError> {
+    let len = params.transfer_size;
+    let mut done = backend.xfer_sync(handle, access, buf, len, offset)?;
+    let mut retries = 0;
+    while done > 0 && done < len {
+        if params.single_xfer_attempt || retries == MAX_RETRY {
+            return Err(IorError::ShortTransfer { done, len });
+        }
+        if params.verbose >= 2 {
+            eprintln!(
+                "WARNING: only {} of {} bytes transferred at offset {}, retrying",
+                done, len, offset
+            );
+        }
+        let n = backend.xfer_sync(
+            handle,
+            access,
+            unsafe { buf.add(done as usize) },
+            len - done,
+            offset + done,
+        )?;
+        if n == 0 {
+            break;
+        }
+        done += n;
+        retries += 1;
+    }
+    Ok(done)
+}
+
+/// With collective transfers, fail on every rank if the transfer failed on
+/// any rank: a rank that returned early would leave the others blocked in
+/// their next `_all` call. Ranks whose own transfer succeeded report
+/// `Cancelled`.
+fn agree_on_xfer<T>(
+    result: Result<T, IorError>,
+    collective: bool,
+    comm: &impl Comm,
+) -> Result<T, IorError> {
+    if !collective {
+        return result;
+    }
+    let failed = comm.all_reduce_value(result.is_err() as i32, ReduceOp::Max);
+    match result {
+        Ok(_) if failed != 0 => Err(IorError::Cancelled),
+        result => result,
+    }
+}
+
 /// Inner I/O loop: write or read data for all segments and offsets.
 ///
+/// Transfers beyond `pair_limit` are skipped. With stonewall wear-out, ranks
+/// that stopped early continue until they reach the maximum pair count of
+/// any rank, so the file has no holes. Once max_time_duration expires the
+/// rank stops issuing transfers, including during the wear-out.
+///
 /// Reference: `ior.c:1757-1914` (WriteOrRead)
+#[allow(clippy::too_many_arguments)]
 fn write_or_read(
     handle: &ior_core::FileHandle,
     access: XferDir,
@@ -208,13 +391,15 @@ fn write_or_read(
     backend: &dyn Aiori,
     rank: i32,
     rank_offset: i32,
-    comm: &SimpleCommunicator,
-) -> Result<(i64, usize), IorError> {
+    pair_limit: u64,
+    comm: &impl Comm,
+) -> Result<PhaseOutcome, IorError> {
     let num_tasks = params.num_tasks;
     let pretend_rank = ((rank + rank_offset) % num_tasks + num_tasks) % num_tasks;
     let offsets_per_block = params.block_size / params.transfer_size;
     let mut data_moved: i64 = 0;
-    let mut errors: usize = 0;
+    let mut verify = VerifyLog::default();
+    let mut latency = LatencyHistogram::new();
 
     // Allocate page-aligned transfer buffer (required for O_DIRECT)
     let buf_size = params.transfer_size as usize;
@@ -233,9 +418,47 @@ fn write_or_read(
     } else {
         None
     };
+    let num_offsets = random_offsets.as_ref().map_or(offsets_per_block, |v| v.len() as i64);
+    let pairs_per_pass = (num_offsets * params.segment_count) as u64;
+    let mut samples = new_sampler(params);
+    let collective = backend.collective_xfer();
+
+    // One transfer at linear index `idx`; returns bytes moved
+    let mut transfer = |idx: u64| -> Result<i64, IorError> {
+        let offset = transfer_offset(params, pretend_rank, random_offsets.as_deref(), idx);
+
+        // Update pattern with offset-specific stamps before write
+        if access == XferDir::Write {
+            data_pattern::update_write_pattern(offset, &mut buffer, seed, pretend_rank, data_type);
+        }
+
+        let xfer_start = now();
+        let transferred =
+            xfer_full(backend, handle, access, buffer.as_mut_ptr(), params, offset)?;
+        let xfer_end = now();
+        latency.record_secs(xfer_end - xfer_start);
+        if let Some(ref mut sampler) = samples {
+            sampler.record(xfer_end, transferred);
+        }
+
+        // READCHECK: verify data after each read (ref: ior.c:1695-1729)
+        if access == XferDir::Read && params.check_read {
+            verify.verify(offset, &buffer, seed, pretend_rank, data_type);
+        }
+
+        if params.fsync_per_write && access == XferDir::Write {
+            backend.fsync(handle)?;
+        }
+        Ok(transferred)
+    };
 
     let start = now();
+    let time_limit = phase_time_limit(params);
+    let timed_out_at = |t: f64| time_limit.is_some_and(|limit| t - start > limit);
     let mut hit_stonewall = false;
+    let mut timed_out = false;
+    let mut pairs: u64 = 0;
+    let shared_stonewall = params.deadline_for_stonewalling > 0 && !params.file_per_proc;
 
     loop {
         // min_time_duration loop (ior.c:1845)
@@ -244,56 +467,21 @@ fn write_or_read(
                 break;
             }
 
-            let num_offsets = random_offsets.as_ref().map_or(offsets_per_block, |v| v.len() as i64);
-
             for j in 0..num_offsets {
-                if hit_stonewall {
+                if hit_stonewall || timed_out || pairs >= pair_limit {
                     break;
                 }
 
-                // OFFSET CALCULATION (ref: ior.c:1823-1829)
-                let offset = if let Some(ref offsets) = random_offsets {
-                    let base = offsets[j as usize];
-                    if params.file_per_proc {
-                        base + seg * params.block_size
-                    } else {
-                        base + seg * num_tasks as i64 * params.block_size
-                    }
-                } else if params.file_per_proc {
-                    j * params.transfer_size + seg * params.block_size
-                } else {
-                    // Shared file: interleaved blocks per rank
-                    j * params.transfer_size
-                        + seg * num_tasks as i64 * params.block_size
-                        + pretend_rank as i64 * params.block_size
-                };
+                let idx = (seg * num_offsets + j) as u64;
+                data_moved += agree_on_xfer(transfer(idx), collective, comm)?;
+                pairs += 1;
 
-                // Update pattern with offset-specific stamps before write
-                if access == XferDir::Write {
-                    data_pattern::update_write_pattern(offset, &mut buffer, seed, pretend_rank, data_type);
-                }
-
-                let transferred = backend.xfer_sync(
-                    handle,
-                    access,
-                    buffer.as_mut_ptr(),
-                    params.transfer_size,
-                    offset,
-                )?;
-                data_moved += transferred;
-
-                // READCHECK: verify data after each read (ref: ior.c:1695-1729)
-                if access == XferDir::Read && params.check_read {
-                    errors += data_pattern::verify_pattern(offset, &buffer, seed, pretend_rank, data_type);
-                }
-
-                if params.fsync_per_write && access == XferDir::Write {
-                    backend.fsync(handle)?;
-                }
+                let t = now();
+                timed_out = timed_out_at(t);
 
                 // Stonewalling check (ref: ior.c:1834-1842)
                 if params.deadline_for_stonewalling > 0 {
-                    let elapsed = now() - start;
+                    let elapsed = t - start;
                     if elapsed > params.deadline_for_stonewalling as f64 {
                         hit_stonewall = true;
                     }
@@ -301,23 +489,77 @@ fn write_or_read(
             }
 
             // Collective stonewalling broadcast once per segment (not per transfer)
-            // file-per-proc: each rank decides independently, no broadcast needed
-            if params.deadline_for_stonewalling > 0 && !params.file_per_proc {
-                let mut stonewall_flag = hit_stonewall as i32;
-                comm.process_at_rank(0)
-                    .broadcast_into(&mut stonewall_flag);
-                hit_stonewall = stonewall_flag != 0;
+            // file-per-proc: each rank decides independently, no broadcast needed.
+            // A rank that timed out keeps joining the broadcasts without I/O.
+            if shared_stonewall {
+                let mut flags = [hit_stonewall as i32, timed_out as i32];
+                comm.broadcast(0, &mut flags);
+                hit_stonewall = flags[0] != 0 || flags[1] != 0;
+            } else if timed_out {
+                break;
             }
         }
 
         // Check min_time_duration
         let elapsed = now() - start;
-        if elapsed >= params.min_time_duration as f64 || params.min_time_duration == 0 {
+        let mut done = [
+            timed_out as i32,
+            (pairs >= pair_limit
+                || elapsed >= params.min_time_duration as f64
+                || params.min_time_duration == 0) as i32,
+        ];
+        // Another pass means more broadcasts: every rank must make the same call
+        if shared_stonewall {
+            let local = done;
+            comm.all_reduce(&local, &mut done, ReduceOp::Max);
+            timed_out = done[0] != 0;
+        }
+        if hit_stonewall || timed_out || done[1] != 0 {
             break;
         }
     }
 
-    Ok((data_moved, errors))
+    let stonewall = (params.deadline_for_stonewalling > 0).then(|| report::StonewallPoint {
+        time: now() - start,
+        pairs,
+        data_moved,
+    });
+
+    // Stonewall wear-out: continue to the max pair count (ref: ior.c:1866-1895)
+    let mut wear_out_pairs = None;
+    if wear_out_enabled(params) {
+        let max_pairs = comm.all_reduce_value(pairs, ReduceOp::Max);
+        if params.verbose > 0 && pairs < max_pairs {
+            eprintln!(
+                "rank {}: stonewall wear-out from {} to {} pairs",
+                rank, pairs, max_pairs
+            );
+        }
+        while pairs_per_pass > 0 && pairs < max_pairs.min(pair_limit) && !timed_out {
+            data_moved += transfer(pairs % pairs_per_pass)?;
+            pairs += 1;
+            timed_out = timed_out_at(now());
+        }
+        wear_out_pairs = Some(max_pairs);
+    }
+
+    if timed_out && params.verbose > 0 {
+        eprintln!(
+            "rank {}: max_time_duration ({} min) expired after {} transfers",
+            rank, params.max_time_duration, pairs
+        );
+    }
+
+    Ok(PhaseOutcome {
+        data_moved,
+        verify,
+        pairs,
+        stonewall,
+        wear_out_pairs,
+        timed_out,
+        latency,
+        samples,
+    })
 }
 
 /// Generate test file name based on rank and offset.
@@ -334,10 +576,13 @@ pub fn get_test_file_name(params: &IorParam, rank: i32, rank_offset: i32) -> Str
     }
 }
 
-/// WRITECHECK: re-read all written data and verify against expected pattern.
+/// WRITECHECK: re-read the written data and verify against expected pattern.
 ///
-/// Opens the file RDONLY, reads all segments × offsets, and verifies each
-/// transfer buffer against the expected data pattern. Returns total error count.
+/// Opens the file RDONLY and reads back the transfers the write phase issued:
+/// the same (possibly random) offsets, limited to the first `pairs` of them
+/// (a stonewalled write leaves the rest unwritten). Each transfer buffer is
+/// verified against the expected data pattern. Returns the errors found.
+/// Collective when random offsets are used on a shared file.
 ///
 /// Reference: C IOR `ior.c:1346-1369`
 fn write_or_read_verify(
@@ -345,11 +590,19 @@ fn write_or_read_verify(
     backend: &dyn Aiori,
     rank: i32,
     rank_offset: i32,
-    _comm: &SimpleCommunicator,
-) -> Result<usize, IorError> {
+    pairs: u64,
+    comm: &impl Comm,
+) -> Result<VerifyLog, IorError> {
     let num_tasks = params.num_tasks;
     let pretend_rank = ((rank + rank_offset) % num_tasks + num_tasks) % num_tasks;
     let offsets_per_block = params.block_size / params.transfer_size;
+    let random_offsets = if params.random_offset {
+        Some(get_offset_array_random(params, pretend_rank, comm))
+    } else {
+        None
+    };
+    let num_offsets = random_offsets.as_ref().map_or(offsets_per_block, |v| v.len() as i64);
+    let pairs_per_pass = (num_offsets * params.segment_count) as u64;
     let seed = params.time_stamp_signature_value;
     let data_type = params.data_packet_type;
 
@@ -362,32 +615,48 @@ fn write_or_read_verify(
 
     let buf_size = params.transfer_size as usize;
     let mut buffer = AlignedBuffer::new(buf_size);
-    let mut errors: usize = 0;
+    let mut log = VerifyLog::default();
+    let collective = backend.collective_xfer();
 
-    for seg in 0..params.segment_count {
-        for j in 0..offsets_per_block {
-            let offset = if params.file_per_proc {
-                j * params.transfer_size + seg * params.block_size
-            } else {
-                j * params.transfer_size
-                    + seg * num_tasks as i64 * params.block_size
-                    + pretend_rank as i64 * params.block_size
-            };
+    for idx in 0..pairs.min(pairs_per_pass) {
+        let offset = transfer_offset(params, pretend_rank, random_offsets.as_deref(), idx);
 
-            backend.xfer_sync(
-                &handle,
-                XferDir::Read,
-                buffer.as_mut_ptr(),
-                params.transfer_size,
-                offset,
-            )?;
+        let read = xfer_full(backend, &handle, XferDir::Read, buffer.as_mut_ptr(), params, offset);
+        agree_on_xfer(read, collective, comm)?;
 
-            errors += data_pattern::verify_pattern(offset, &buffer, seed, pretend_rank, data_type);
-        }
+        log.verify(offset, &buffer, seed, pretend_rank, data_type);
     }
 
     backend.close(handle)?;
-    Ok(errors)
+    Ok(log)
+}
+
+/// Sum one check's verification errors over all ranks, gather the logged
+/// mismatches to rank 0 and report them there.
+fn reduce_check_errors(
+    check: &str,
+    log: &VerifyLog,
+    params: &IorParam,
+    rank: i32,
+    rank_offset: i32,
+    comm: &impl Comm,
+) -> (u64, Vec<verify::MismatchReport>) {
+    let total = comm.all_reduce_value(log.errors as u64, ReduceOp::Sum);
+    if total == 0 {
+        if comm.rank() == 0 && params.verbose > 0 {
+            eprintln!("INFO: {} passed (0 errors)", check);
+        }
+        return (0, Vec::new());
+    }
+
+    let num_tasks = params.num_tasks;
+    let pretend_rank = ((rank + rank_offset) % num_tasks + num_tasks) % num_tasks;
+    let mismatches = verify::gather_mismatches(log, params, pretend_rank, comm);
+    if comm.rank() == 0 {
+        eprintln!("WARNING: {} found {} data errors", check, total);
+        verify::print_mismatches(&mismatches);
+    }
+    (total, mismatches)
 }
 
 /// Remove test files.
@@ -415,8 +684,8 @@ fn reduce_and_report(
     access: &str,
     timers: &BenchTimers,
     params: &IorParam,
-    data_moved: i64,
-    comm: &SimpleCommunicator,
+    outcome: &PhaseOutcome,
+    comm: &impl Comm,
     rep: i32,
     print_text: bool,
 ) -> Option<report::IterResult> {
@@ -424,10 +693,10 @@ fn reduce_and_report(
     let reduced = report::reduce_timers(timers, comm);
 
     // 2. Aggregate data moved
-    let agg_data = report::reduce_data_moved(data_moved, comm);
+    let agg_data = report::reduce_data_moved(outcome.data_moved, comm);
 
     // 3. Compute metrics
-    let result = report::compute_metrics(
+    let mut result = report::compute_metrics(
         &reduced,
         timers,
         agg_data,
@@ -436,6 +705,19 @@ fn reduce_and_report(
         comm,
         rep,
     );
+    result.stonewall = outcome
+        .stonewall
+        .as_ref()
+        .map(|point| report::reduce_stonewall(point, comm));
+    result.truncated = report::reduce_any(outcome.timed_out, comm);
+    result.latency_hist = report::reduce_latency(&outcome.latency, comm);
+    if params.sample_interval > 0.0 {
+        result.time_series = Some(report::reduce_time_series(
+            outcome.samples.as_ref(),
+            params.sample_interval,
+            comm,
+        ));
+    }
 
     // 4. Print result (rank 0 only)
     if print_text {
@@ -454,18 +736,15 @@ fn check_file_size(
     data_moved: i64,
     rank: i32,
     rank_offset: i32,
-    comm: &SimpleCommunicator,
+    comm: &impl Comm,
 ) {
     let path = get_test_file_name(params, rank, rank_offset);
     let local_size = backend.get_file_size(&path).unwrap_or(0);
 
     if params.file_per_proc {
         // Each rank checks its own file; aggregate with SUM
-        let mut agg_size: i64 = 0;
-        comm.all_reduce_into(&local_size, &mut agg_size, SystemOperation::sum());
-
-        let mut agg_xfer: i64 = 0;
-        comm.all_reduce_into(&data_moved, &mut agg_xfer, SystemOperation::sum());
+        let agg_size = comm.all_reduce_value(local_size, ReduceOp::Sum);
+        let agg_xfer = comm.all_reduce_value(data_moved, ReduceOp::Sum);
 
         if comm.rank() == 0 && params.verbose > 0 && agg_size < agg_xfer {
             eprintln!(
@@ -475,10 +754,8 @@ fn check_file_size(
         }
     } else {
         // Shared file: verify consistency
-        let mut min_size: i64 = 0;
-        let mut max_size: i64 = 0;
-        comm.all_reduce_into(&local_size, &mut min_size, SystemOperation::min());
-        comm.all_reduce_into(&local_size, &mut max_size, SystemOperation::max());
+        let min_size = comm.all_reduce_value(local_size, ReduceOp::Min);
+        let max_size = comm.all_reduce_value(local_size, ReduceOp::Max);
 
         if comm.rank() == 0 && params.verbose > 0 && min_size != max_size {
             eprintln!(
@@ -487,8 +764,7 @@ fn check_file_size(
             );
         }
 
-        let mut agg_xfer: i64 = 0;
-        comm.all_reduce_into(&data_moved, &mut agg_xfer, SystemOperation::sum());
+        let agg_xfer = comm.all_reduce_value(data_moved, ReduceOp::Sum);
 
         if comm.rank() == 0 && params.verbose > 0 && min_size < agg_xfer {
             eprintln!(
@@ -499,6 +775,16 @@ fn check_file_size(
     }
 }
 
+/// Rank shift for `-C`: `task_per_node_offset` whole nodes, so that no rank
+/// reads back data written (and cached) on its own node. Without detected
+/// nodes this is a shift by `task_per_node_offset` ranks.
+///
+/// Reference: `ior.c:1389-1396`
+fn reorder_offset(params: &IorParam) -> i32 {
+    let tasks_per_node = params.num_tasks_on_node0.max(1);
+    (params.task_per_node_offset * tasks_per_node) % params.num_tasks
+}
+
 /// Generate a pseudo-random rank offset for task reordering.
 fn random_rank_offset(rank: i32, num_tasks: i32, seed: i32) -> i32 {
     // Simple LCG to get deterministic but shuffled offset per rank
@@ -517,7 +803,7 @@ fn random_rank_offset(rank: i32, num_tasks: i32, seed: i32) -> i32 {
 fn get_offset_array_random(
     params: &IorParam,
     pretend_rank: i32,
-    comm: &SimpleCommunicator,
+    comm: &impl Comm,
 ) -> Vec<i64> {
     let offsets_per_block = params.block_size / params.transfer_size;
 
@@ -529,7 +815,7 @@ fn get_offset_array_random(
     }
     if !params.file_per_proc {
         let mut seed_i64 = seed as i64;
-        comm.process_at_rank(0).broadcast_into(&mut seed_i64);
+        comm.broadcast_value(0, &mut seed_i64);
         seed = seed_i64 as u64;
     }
 
@@ -563,10 +849,8 @@ fn get_offset_array_random(
             let assigned_rank = ((state >> 33) as i32).rem_euclid(params.num_tasks);
             if assigned_rank == pretend_rank {
                 let j = (xfer_idx as i64) % offsets_per_block;
-                let rank_of_xfer = (xfer_idx as i64) / offsets_per_block;
-                let offset = j * params.transfer_size
-                    + rank_of_xfer * params.block_size;
-                offsets.push(offset);
+                let rank_of_xfer = ((xfer_idx as i64) / offsets_per_block) as i32;
+                offsets.push(shared_slot_offset(params, rank_of_xfer, j));
             }
         }
 
@@ -605,7 +889,7 @@ fn fisher_yates_shuffle(arr: &mut [i64], seed: u64) {
 pub fn run_benchmark_async(
     params: &IorParam,
     backend: &dyn Aiori,
-    comm: &SimpleCommunicator,
+    comm: &impl Comm,
     print_text: bool,
 ) -> Result<BenchmarkResults, IorError> {
     let rank = comm.rank();
@@ -620,6 +904,7 @@ pub fn run_benchmark_async(
 
     for rep in 0..params.repetitions {
         let mut rank_offset: i32 = 0;
+        let mut wear_out_pairs: Option<u64> = None;
 
         // === WRITE PHASE ===
         if params.write_file {
@@ -652,15 +937,18 @@ pub fn run_benchmark_async(
             }
 
             timers.timers[2] = now();
-            let data_moved = write_or_read_async(
+            let outcome = write_or_read_async(
                 &handle,
                 XferDir::Write,
                 params,
                 backend,
                 rank,
                 rank_offset,
+                pair_limit(params, None),
                 comm,
             )?;
+            let data_moved = outcome.data_moved;
+            wear_out_pairs = outcome.wear_out_pairs;
             timers.timers[3] = now();
 
             if params.intra_test_barriers {
@@ -678,24 +966,25 @@ pub fn run_benchmark_async(
             comm.barrier();
             check_file_size(params, backend, data_moved, rank, rank_offset, comm);
 
-            let result = reduce_and_report("write", &timers, params, data_moved, comm, rep, print_text);
-            if let Some(r) = result {
-                write_results.push(r);
-            }
+            let mut result =
+                reduce_and_report("write", &timers, params, &outcome, comm, rep, print_text);
 
             // === WRITECHECK PHASE (async) ===
             if params.check_write {
                 comm.barrier();
-                let errors = write_or_read_verify(params, backend, rank, rank_offset, comm)?;
-                let mut total_errors: usize = 0;
-                comm.all_reduce_into(&errors, &mut total_errors, SystemOperation::sum());
-                if rank == 0 && total_errors > 0 {
-                    eprintln!("WARNING: WRITECHECK found {} data errors", total_errors);
-                } else if rank == 0 && params.verbose > 0 {
-                    eprintln!("INFO: WRITECHECK passed (0 errors)");
+                let log =
+                    write_or_read_verify(params, backend, rank, rank_offset, outcome.pairs, comm)?;
+                let (total, mismatches) =
+                    reduce_check_errors("WRITECHECK", &log, params, rank, rank_offset, comm);
+                if let Some(r) = result.as_mut() {
+                    r.verify_errors = Some(total);
+                    r.mismatches = mismatches;
                 }
                 comm.barrier();
             }
+            if let Some(r) = result {
+                write_results.push(r);
+            }
         }
 
         // === READ PHASE ===
@@ -708,7 +997,7 @@ pub fn run_benchmark_async(
             }
 
             if params.reorder_tasks {
-                rank_offset = params.task_per_node_offset % num_tasks;
+                rank_offset = reorder_offset(params);
             } else if params.reorder_tasks_random {
                 rank_offset = random_rank_offset(rank, num_tasks, params.reorder_tasks_random_seed);
             }
@@ -731,13 +1020,14 @@ pub fn run_benchmark_async(
             }
 
             timers.timers[2] = now();
-            let data_moved = write_or_read_async(
+            let outcome = write_or_read_async(
                 &handle,
                 XferDir::Read,
                 params,
                 backend,
                 rank,
                 rank_offset,
+                pair_limit(params, wear_out_pairs),
                 comm,
             )?;
             timers.timers[3] = now();
@@ -750,7 +1040,18 @@ pub fn run_benchmark_async(
             backend.close(handle)?;
             timers.timers[5] = now();
 
-            let result = reduce_and_report("read", &timers, params, data_moved, comm, rep, print_text);
+            let mut result =
+                reduce_and_report("read", &timers, params, &outcome, comm, rep, print_text);
+
+            // READCHECK: buffers were verified as their reads completed
+            if params.check_read {
+                let (total, mismatches) =
+                    reduce_check_errors("READCHECK", &outcome.verify, params, rank, rank_offset, comm);
+                if let Some(r) = result.as_mut() {
+                    r.verify_errors = Some(total);
+                    r.mismatches = mismatches;
+                }
+            }
             if let Some(r) = result {
                 read_results.push(r);
             }
@@ -793,29 +1094,253 @@ pub fn run_benchmark_async(
 /// Callbacks fire on the poll() caller thread (same thread as the submit/poll
 /// loop), so plain `Cell` suffices — no atomics needed.
 struct AsyncState {
-    completed_count: Cell<usize>,
     total_bytes: Cell<i64>,
-    error: Cell<i64>,
+    /// Transfers completed in full (cancelled and failed ones excluded).
+    completed: Cell<u64>,
+    error: Cell<Option<IorError>>,
+    /// Indices of slots whose buffer is not in flight.
+    free_slots: RefCell<Vec<usize>>,
+    /// Indices of slots whose transfer was short and must be resubmitted.
+    retry_slots: RefCell<Vec<usize>>,
+    /// Requested length of every transfer.
+    xfer_len: i64,
+    /// Treat a short transfer as an error instead of retrying it.
+    single_attempt: bool,
+    /// Submit-to-completion latency of successful transfers.
+    latency: RefCell<LatencyHistogram>,
+    /// Bytes completed per sample interval.
+    samples: RefCell<Option<BandwidthSampler>>,
+    /// Pattern to verify completed reads against (READCHECK only):
+    /// signature seed, pretend rank and packet type.
+    verify: Option<(i32, i32, data_pattern::DataPacketType)>,
+    /// Verification errors found so far.
+    log: RefCell<VerifyLog>,
+}
+
+/// One transfer buffer of the async pipeline. Its address is passed as
+/// `user_data`, so a buffer is only reused after its own completion fires.
+struct AsyncSlot {
+    buffer: AlignedBuffer,
+    index: usize,
+    /// Token of the transfer currently using this buffer.
+    token: Cell<Option<XferToken>>,
+    /// Time the current transfer was submitted.
+    submitted_at: Cell<f64>,
+    /// File offset of the current transfer.
+    offset: Cell<i64>,
+    /// Bytes of the current transfer completed by earlier (short) attempts.
+    done: Cell<i64>,
+    retries: Cell<u32>,
+    state: *const AsyncState,
 }
 
 /// C-compatible callback for async transfer completion.
 extern "C" fn async_completion_callback(result: *const XferResult) {
     unsafe {
         let res = &*result;
-        let state = &*(res.user_data as *const AsyncState);
+        let slot = &*(res.user_data as *const AsyncSlot);
+        let state = &*slot.state;
+        slot.token.set(None);
         if res.error == 0 {
             state.total_bytes.set(state.total_bytes.get() + res.bytes_transferred);
-        } else {
-            state.error.set(res.error as i64);
+            let t = now();
+            if let Some(ref mut sampler) = *state.samples.borrow_mut() {
+                sampler.record(t, res.bytes_transferred);
+            }
+
+            // Short transfer: resubmit the remainder unless told not to
+            let done = slot.done.get() + res.bytes_transferred;
+            let short = res.bytes_transferred > 0 && done < state.xfer_len;
+            if short && !state.single_attempt && slot.retries.get() < MAX_RETRY {
+                slot.done.set(done);
+                slot.retries.set(slot.retries.get() + 1);
+                state.retry_slots.borrow_mut().push(slot.index);
+                return;
+            }
+
+            if short {
+                let len = state.xfer_len;
+                state.error.set(Some(IorError::ShortTransfer { done, len }));
+            } else {
+                state.completed.set(state.completed.get() + 1);
+                state.latency.borrow_mut().record_secs(t - slot.submitted_at.get());
+                // READCHECK: verify the buffer before it is reused
+                if let Some((seed, pretend_rank, data_type)) = state.verify {
+                    state.log.borrow_mut().verify(
+                        slot.offset.get(),
+                        &slot.buffer,
+                        seed,
+                        pretend_rank,
+                        data_type,
+                    );
+                }
+            }
+        } else if res.error != libc::ECANCELED {
+            // Cancellation after a timeout is expected, not an I/O error
+            state.error.set(Some(IorError::Io(res.error)));
+        }
+        slot.done.set(0);
+        slot.retries.set(0);
+        state.free_slots.borrow_mut().push(slot.index);
+    }
+}
+
+/// Submit/poll pipeline state shared across the timed loop and the wear-out.
+struct AsyncPipeline<'a> {
+    handle: &'a ior_core::FileHandle,
+    access: XferDir,
+    params: &'a IorParam,
+    backend: &'a dyn Aiori,
+    pretend_rank: i32,
+    random_offsets: Option<Vec<i64>>,
+    slots: Vec<AsyncSlot>,
+    state: &'a AsyncState,
+    /// Phase start and hard limit (max_time_duration) in seconds.
+    start: f64,
+    time_limit: Option<f64>,
+    timed_out: bool,
+}
+
+impl AsyncPipeline<'_> {
+    /// Submit transfers `first..end` (indices wrap every `pairs_per_pass`),
+    /// keeping up to queue_depth in flight, then drain. Stops submitting once
+    /// `deadline` (seconds since start) passes. When max_time_duration expires,
+    /// in-flight transfers are cancelled and drained. Returns the number
+    /// completed in full.
+    fn run(
+        &mut self,
+        first: u64,
+        end: u64,
+        pairs_per_pass: u64,
+        deadline: Option<f64>,
+    ) -> Result<u64, IorError> {
+        let queue_depth = self.slots.len();
+        let seed = self.params.time_stamp_signature_value;
+        let data_type = self.params.data_packet_type;
+        let mut idx = first;
+        let mut error = None;
+        let completed_before = self.state.completed.get();
+
+        loop {
+            // Resubmit the remainder of short transfers, or give up on them
+            let retry: Vec<usize> = self.state.retry_slots.borrow_mut().drain(..).collect();
+            for slot_idx in retry {
+                let slot = &mut self.slots[slot_idx];
+                if self.timed_out || error.is_some() {
+                    slot.done.set(0);
+                    slot.retries.set(0);
+                    self.state.free_slots.borrow_mut().push(slot_idx);
+                    continue;
+                }
+                let done = slot.done.get();
+                let submitted = self.backend.xfer_submit(
+                    self.handle,
+                    self.access,
+                    unsafe { slot.buffer.as_mut_ptr().add(done as usize) },
+                    self.params.transfer_size - done,
+                    slot.offset.get() + done,
+                    slot as *const AsyncSlot as usize,
+                    async_completion_callback,
+                );
+                match submitted {
+                    Ok(token) => slot.token.set(Some(token)),
+                    Err(e) => {
+                        slot.done.set(0);
+                        slot.retries.set(0);
+                        self.state.free_slots.borrow_mut().push(slot_idx);
+                        error = Some(e);
+                    }
+                }
+            }
+
+            // Submit burst: fill pipeline up to queue_depth
+            while idx < end && !self.timed_out && error.is_none() {
+                // Check stonewalling
+                if deadline.is_some_and(|d| now() - self.start > d) {
+                    break;
+                }
+                let Some(slot_idx) = self.state.free_slots.borrow_mut().pop() else {
+                    break;
+                };
+
+                let offset = transfer_offset(
+                    self.params,
+                    self.pretend_rank,
+                    self.random_offsets.as_deref(),
+                    idx % pairs_per_pass,
+                );
+
+                // Update pattern with offset-specific stamps before write
+                let slot = &mut self.slots[slot_idx];
+                if self.access == XferDir::Write {
+                    data_pattern::update_write_pattern(offset, &mut slot.buffer, seed, self.pretend_rank, data_type);
+                }
+
+                slot.submitted_at.set(now());
+                slot.offset.set(offset);
+                let submitted = self.backend.xfer_submit(
+                    self.handle,
+                    self.access,
+                    slot.buffer.as_mut_ptr(),
+                    self.params.transfer_size,
+                    offset,
+                    slot as *const AsyncSlot as usize,
+                    async_completion_callback,
+                );
+                match submitted {
+                    Ok(token) => slot.token.set(Some(token)),
+                    Err(e) => {
+                        self.state.free_slots.borrow_mut().push(slot_idx);
+                        error = Some(e);
+                        break;
+                    }
+                }
+                idx += 1;
+            }
+
+            // No more work and nothing in flight
+            let in_flight = queue_depth - self.state.free_slots.borrow().len();
+            if in_flight == 0 {
+                break;
+            }
+
+            // Poll for completions
+            if let Err(e) = self.backend.poll(queue_depth) {
+                // Cannot drain without poll; leak buffers the backend may still use
+                std::mem::forget(std::mem::take(&mut self.slots));
+                return Err(e);
+            }
+
+            // Check for errors (stop submitting, drain what is in flight)
+            if let Some(e) = self.state.error.take()
+                && error.is_none()
+            {
+                error = Some(e);
+            }
+
+            // Hard timeout: cancel everything still in flight, then drain
+            if !self.timed_out && self.time_limit.is_some_and(|l| now() - self.start > l) {
+                self.timed_out = true;
+                for slot in &self.slots {
+                    if let Some(token) = slot.token.get() {
+                        let _ = self.backend.cancel(token);
+                    }
+                }
+            }
+        }
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(self.state.completed.get() - completed_before),
         }
-        state.completed_count.set(state.completed_count.get() + 1);
     }
 }
 
 /// Inner async I/O loop with pipeline pattern.
 ///
 /// Each rank runs its own async pipeline independently; MPI synchronization
-/// occurs at phase boundaries.
+/// occurs at phase boundaries (and for the stonewall wear-out reduction).
+#[allow(clippy::too_many_arguments)]
 fn write_or_read_async(
     handle: &ior_core::FileHandle,
     access: XferDir,
@@ -823,147 +1348,380 @@ fn write_or_read_async(
     backend: &dyn Aiori,
     rank: i32,
     rank_offset: i32,
-    _comm: &SimpleCommunicator,
-) -> Result<i64, IorError> {
+    pair_limit: u64,
+    comm: &impl Comm,
+) -> Result<PhaseOutcome, IorError> {
     let num_tasks = params.num_tasks;
     let pretend_rank = ((rank + rank_offset) % num_tasks + num_tasks) % num_tasks;
     let offsets_per_block = params.block_size / params.transfer_size;
     let queue_depth = params.queue_depth as usize;
-
-    // Calculate total number of transfers
-    let total_xfers = (params.segment_count * offsets_per_block) as usize;
     let seed = params.time_stamp_signature_value;
     let data_type = params.data_packet_type;
 
+    // Completion state — lives on the stack; callbacks fire on this same thread.
+    let state = AsyncState {
+        total_bytes: Cell::new(0),
+        completed: Cell::new(0),
+        error: Cell::new(None),
+        free_slots: RefCell::new((0..queue_depth).rev().collect()),
+        retry_slots: RefCell::new(Vec::new()),
+        xfer_len: params.transfer_size,
+        single_attempt: params.single_xfer_attempt,
+        latency: RefCell::new(LatencyHistogram::new()),
+        samples: RefCell::new(new_sampler(params)),
+        verify: (access == XferDir::Read && params.check_read)
+            .then_some((seed, pretend_rank, data_type)),
+        log: RefCell::new(VerifyLog::default()),
+    };
+
     // Allocate queue_depth page-aligned buffers (required for O_DIRECT)
     let buf_size = params.transfer_size as usize;
-    let mut buffers: Vec<AlignedBuffer> = (0..queue_depth)
-        .map(|_| {
-            let mut buf = AlignedBuffer::new(buf_size);
+    let slots: Vec<AsyncSlot> = (0..queue_depth)
+        .map(|index| {
+            let mut buffer = AlignedBuffer::new(buf_size);
             if access == XferDir::Write {
-                data_pattern::generate_memory_pattern(&mut buf, seed, pretend_rank, data_type);
+                data_pattern::generate_memory_pattern(&mut buffer, seed, pretend_rank, data_type);
+            }
+            AsyncSlot {
+                buffer,
+                index,
+                token: Cell::new(None),
+                submitted_at: Cell::new(0.0),
+                offset: Cell::new(0),
+                done: Cell::new(0),
+                retries: Cell::new(0),
+                state: &state,
             }
-            buf
         })
         .collect();
 
-    // Completion state — lives on the stack; callbacks fire on this same thread.
-    let state = AsyncState {
-        completed_count: Cell::new(0),
-        total_bytes: Cell::new(0),
-        error: Cell::new(0),
-    };
-    let state_ptr = &state as *const AsyncState as usize;
-
     // Pre-compute random offsets if requested
     let random_offsets = if params.random_offset {
-        Some(get_offset_array_random(params, pretend_rank, _comm))
+        Some(get_offset_array_random(params, pretend_rank, comm))
     } else {
         None
     };
 
-    // For random offsets, total_xfers may differ per rank in shared file mode
-    let total_xfers = if let Some(ref offsets) = random_offsets {
-        (offsets.len() as i64 * params.segment_count) as usize
-    } else {
-        total_xfers
+    // For random offsets, the transfer count may differ per rank in shared file mode
+    let num_offsets = random_offsets.as_ref().map_or(offsets_per_block, |v| v.len() as i64);
+    let pairs_per_pass = (num_offsets * params.segment_count) as u64;
+    let pass_pairs = pairs_per_pass.min(pair_limit);
+
+    let mut pipeline = AsyncPipeline {
+        handle,
+        access,
+        params,
+        backend,
+        pretend_rank,
+        random_offsets,
+        slots,
+        state: &state,
+        start: now(),
+        time_limit: phase_time_limit(params),
+        timed_out: false,
     };
 
-    let start = now();
-    let mut submitted: usize = 0;
-    let mut completed: usize = 0;
-    let mut in_flight: usize = 0;
-    let mut buf_idx: usize = 0;
-
-    // Generate offset for a given linear transfer index
-    let calc_offset = |xfer_idx: usize| -> i64 {
-        if let Some(ref offsets) = random_offsets {
-            let num_per_seg = offsets.len();
-            let seg = xfer_idx / num_per_seg;
-            let j = xfer_idx % num_per_seg;
-            let base = offsets[j];
-            if params.file_per_proc {
-                base + seg as i64 * params.block_size
-            } else {
-                base + seg as i64 * num_tasks as i64 * params.block_size
-            }
-        } else {
-            let seg = xfer_idx as i64 / offsets_per_block;
-            let j = xfer_idx as i64 % offsets_per_block;
-            if params.file_per_proc {
-                j * params.transfer_size + seg * params.block_size
-            } else {
-                j * params.transfer_size
-                    + seg * num_tasks as i64 * params.block_size
-                    + pretend_rank as i64 * params.block_size
-            }
-        }
-    };
+    let deadline = (params.deadline_for_stonewalling > 0)
+        .then_some(params.deadline_for_stonewalling as f64);
+    let start = pipeline.start;
+    let mut pairs: u64 = 0;
 
     loop {
-        // Submit burst: fill pipeline up to queue_depth
-        while in_flight < queue_depth && submitted < total_xfers {
-            // Check stonewalling
-            if params.deadline_for_stonewalling > 0 {
-                let elapsed = now() - start;
-                if elapsed > params.deadline_for_stonewalling as f64 {
-                    break;
-                }
-            }
+        let completed = pipeline.run(0, pass_pairs, pairs_per_pass, deadline)?;
+        pairs += completed;
 
-            let offset = calc_offset(submitted);
+        // Check min_time_duration restart (another pass unless stonewalled)
+        let elapsed = now() - start;
+        if completed < pass_pairs
+            || pass_pairs == 0
+            || params.min_time_duration == 0
+            || elapsed >= params.min_time_duration as f64
+        {
+            break;
+        }
+    }
 
-            // Update pattern with offset-specific stamps before write
-            if access == XferDir::Write {
-                data_pattern::update_write_pattern(offset, &mut buffers[buf_idx], seed, pretend_rank, data_type);
-            }
+    let stonewall = deadline.map(|_| report::StonewallPoint {
+        time: now() - start,
+        pairs,
+        data_moved: state.total_bytes.get(),
+    });
+
+    // Stonewall wear-out: continue to the max pair count of any rank
+    let mut wear_out_pairs = None;
+    if wear_out_enabled(params) {
+        let max_pairs = comm.all_reduce_value(pairs, ReduceOp::Max);
+        if params.verbose > 0 && pairs < max_pairs {
+            eprintln!(
+                "rank {}: stonewall wear-out from {} to {} pairs",
+                rank, pairs, max_pairs
+            );
+        }
+        let target = max_pairs.min(pair_limit);
+        if pairs_per_pass > 0 && pairs < target {
+            pairs += pipeline.run(pairs, target, pairs_per_pass, None)?;
+        }
+        wear_out_pairs = Some(max_pairs);
+    }
 
-            let buf = buffers[buf_idx].as_mut_ptr();
+    if pipeline.timed_out && params.verbose > 0 {
+        eprintln!(
+            "rank {}: max_time_duration ({} min) expired, in-flight transfers cancelled",
+            rank, params.max_time_duration
+        );
+    }
 
-            backend.xfer_submit(
-                handle,
-                access,
-                buf,
-                params.transfer_size,
-                offset,
-                state_ptr,
-                async_completion_callback,
-            )?;
+    Ok(PhaseOutcome {
+        data_moved: state.total_bytes.get(),
+        verify: state.log.take(),
+        pairs,
+        stonewall,
+        wear_out_pairs,
+        timed_out: pipeline.timed_out,
+        latency: state.latency.take(),
+        samples: state.samples.take(),
+    })
+}
 
-            submitted += 1;
-            in_flight += 1;
-            buf_idx = (buf_idx + 1) % queue_depth;
-        }
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
 
-        // No more work and nothing in flight
-        if in_flight == 0 {
-            break;
+    use ior_backend_memory::{MemoryBackend, Namespace};
+
+    use super::*;
+
+    fn shared_params(layout: AccessLayout) -> IorParam {
+        IorParam {
+            block_size: 4096,
+            transfer_size: 1024,
+            segment_count: 2,
+            num_tasks: 3,
+            layout,
+            ..Default::default()
         }
+    }
 
-        // Poll for completions
-        let _n = backend.poll(queue_depth)?;
-        let new_completed = state.completed_count.get();
-        let delta = new_completed - completed;
-        completed = new_completed;
-        in_flight -= delta;
+    /// Offsets of every rank's transfers, in rank-major order.
+    fn all_offsets(params: &IorParam) -> Vec<Vec<i64>> {
+        let pairs = (params.block_size / params.transfer_size * params.segment_count) as u64;
+        (0..params.num_tasks)
+            .map(|rank| (0..pairs).map(|idx| transfer_offset(params, rank, None, idx)).collect())
+            .collect()
+    }
 
-        // Check for errors
-        let err = state.error.get();
-        if err != 0 {
-            return Err(IorError::Io(err as i32));
+    #[test]
+    fn test_segmented_offsets() {
+        let offsets = all_offsets(&shared_params(AccessLayout::Segmented));
+        assert_eq!(offsets[1][..4], [4096, 5120, 6144, 7168]);
+        // Second segment starts after all ranks' blocks
+        assert_eq!(offsets[0][4], 3 * 4096);
+    }
+
+    #[test]
+    fn test_strided_offsets() {
+        let params = shared_params(AccessLayout::Strided);
+        let offsets = all_offsets(&params);
+        assert_eq!(offsets[1][..4], [1024, 4096, 7168, 10240]);
+        assert_eq!(offsets[2][4], 3 * 4096 + 2048);
+
+        // Every transfer slot of the file is covered exactly once
+        let mut flat: Vec<i64> = offsets.concat();
+        flat.sort();
+        let expected: Vec<i64> = (0..flat.len() as i64).map(|i| i * 1024).collect();
+        assert_eq!(flat, expected);
+    }
+
+    #[test]
+    fn test_reorder_offset_shifts_by_nodes() {
+        let params = IorParam {
+            num_tasks: 8,
+            num_tasks_on_node0: 4,
+            ..Default::default()
+        };
+        assert_eq!(reorder_offset(&params), 4);
+        // Single node: every shift lands on the same node
+        let params = IorParam {
+            num_tasks_on_node0: 8,
+            ..params
+        };
+        assert_eq!(reorder_offset(&params), 0);
+        let params = IorParam {
+            num_tasks: 6,
+            num_tasks_on_node0: 2,
+            task_per_node_offset: 2,
+            ..Default::default()
+        };
+        assert_eq!(reorder_offset(&params), 4);
+    }
+
+    /// Write/read with -W/-R on three ranks.
+    fn threaded_params() -> IorParam {
+        IorParam {
+            block_size: 64 * 1024,
+            transfer_size: 16 * 1024,
+            segment_count: 2,
+            num_tasks: 3,
+            write_file: true,
+            read_file: true,
+            check_write: true,
+            check_read: true,
+            ..Default::default()
         }
+    }
+
+    /// Run a full benchmark on three thread ranks sharing a memory file
+    /// system, and return rank 0's results.
+    fn run_threaded(params: &IorParam, options: &[(&str, &str)]) -> BenchmarkResults {
+        try_run_threaded(params, options).unwrap()
+    }
 
-        // Check min_time_duration restart
-        if submitted >= total_xfers && in_flight == 0 {
-            let elapsed = now() - start;
-            if params.min_time_duration > 0 && elapsed < params.min_time_duration as f64 {
-                // Reset for another pass
-                submitted = 0;
+    fn try_run_threaded(
+        params: &IorParam,
+        options: &[(&str, &str)],
+    ) -> Result<BenchmarkResults, IorError> {
+        let mut backend_options = ior_core::BackendOptions::new();
+        for (key, value) in options {
+            backend_options.insert(key.to_string(), ior_core::OptionValue::Str(value.to_string()));
+        }
+        let namespace = Arc::new(Namespace::default());
+        ior_core::ThreadComm::run(3, |comm| {
+            let backend = MemoryBackend::with_namespace(Arc::clone(&namespace));
+            let mut backend = ior_core::with_faults(Box::new(backend), &backend_options);
+            backend.configure(&backend_options).unwrap();
+            if params.queue_depth > 1 {
+                run_benchmark_async(params, backend.as_ref(), &comm, false)
             } else {
-                break;
+                run_benchmark(params, backend.as_ref(), &comm, false)
             }
+        })
+        .swap_remove(0)
+    }
+
+    #[test]
+    fn test_threaded_benchmark() {
+        let cases = [
+            threaded_params(),
+            IorParam {
+                layout: AccessLayout::Strided,
+                random_offset: true,
+                ..threaded_params()
+            },
+            IorParam {
+                file_per_proc: true,
+                queue_depth: 4,
+                ..threaded_params()
+            },
+            IorParam {
+                reorder_tasks: true,
+                queue_depth: 4,
+                ..threaded_params()
+            },
+        ];
+        for params in cases {
+            let results = run_threaded(&params, &[]);
+            assert_eq!(results.write_results.len(), 1);
+            assert_eq!(results.read_results.len(), 1);
+            for result in results.write_results.iter().chain(&results.read_results) {
+                assert_eq!(result.data_moved, 3 * 2 * 64 * 1024);
+                assert_eq!(result.verify_errors, Some(0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_verification_reports_lost_writes() {
+        // Writes are dropped, so every nonzero word reads back as zero
+        let results = run_threaded(&threaded_params(), &[("memory.discard", "1")]);
+        let write = &results.write_results[0];
+        assert!(write.verify_errors.unwrap() > 0);
+        assert!(!write.mismatches.is_empty());
+        assert!(write.mismatches.iter().all(|m| m.kind == ior_core::MismatchKind::Zero));
+    }
+
+    #[test]
+    fn test_collective_xfer_error_fails_every_rank() {
+        let results = ior_core::ThreadComm::run(3, |comm| {
+            let result = if comm.rank() == 1 {
+                Err(IorError::ShortTransfer { done: 1, len: 2 })
+            } else {
+                Ok(2i64)
+            };
+            agree_on_xfer(result, true, &comm)
+        });
+        assert!(matches!(results[0], Err(IorError::Cancelled)));
+        assert!(matches!(results[1], Err(IorError::ShortTransfer { done: 1, len: 2 })));
+        assert!(matches!(results[2], Err(IorError::Cancelled)));
+
+        // Independent transfers fail on their own rank only
+        let results = ior_core::ThreadComm::run(2, |comm| {
+            let result = if comm.rank() == 1 { Err(IorError::NotFound) } else { Ok(0) };
+            agree_on_xfer(result, false, &comm)
+        });
+        assert!(matches!(results[0], Ok(0)));
+    }
+
+    #[test]
+    fn test_stonewall_wear_out_leaves_no_holes() {
+        let params = IorParam {
+            segment_count: 1000,
+            deadline_for_stonewalling: 1,
+            stonewall_wear_out: true,
+            ..threaded_params()
+        };
+        // 1ms per transfer: 4000 transfers per rank cannot finish in time
+        let results = run_threaded(&params, &[("memory.latency_us", "1000")]);
+        let write = &results.write_results[0];
+        let stonewall = write.stonewall.as_ref().unwrap();
+        assert!(write.data_moved < 3 * 1000 * 64 * 1024);
+        // Wear-out brings every rank to the same transfer count
+        assert_eq!(write.data_moved, 3 * stonewall.pairs_max as i64 * 16 * 1024);
+        assert_eq!(write.verify_errors, Some(0));
+        assert_eq!(results.read_results[0].verify_errors, Some(0));
+    }
+
+    #[test]
+    fn test_short_transfers_are_retried() {
+        let faults = [("faults.short", "0.5"), ("faults.seed", "1")];
+        for queue_depth in [1, 4] {
+            let params = IorParam {
+                queue_depth,
+                ..threaded_params()
+            };
+            let results = run_threaded(&params, &faults);
+            let write = &results.write_results[0];
+            assert_eq!(write.data_moved, 3 * 2 * 64 * 1024);
+            assert_eq!(write.verify_errors, Some(0));
+            assert_eq!(results.read_results[0].verify_errors, Some(0));
+
+            // -x: a short transfer fails the test instead
+            let params = IorParam {
+                single_xfer_attempt: true,
+                ..params
+            };
+            let faults = [("faults.short", "1")];
+            assert!(matches!(
+                try_run_threaded(&params, &faults),
+                Err(IorError::ShortTransfer { .. })
+            ));
         }
     }
 
-    Ok(state.total_bytes.get())
+    #[test]
+    fn test_injected_faults_reach_verification() {
+        let params = IorParam {
+            queue_depth: 4,
+            ..threaded_params()
+        };
+        // Late and reordered completions are harmless
+        let faults = [("faults.delay_us", "200"), ("faults.reorder", "1")];
+        let results = run_threaded(&params, &faults);
+        assert_eq!(results.write_results[0].verify_errors, Some(0));
+        assert_eq!(results.read_results[0].verify_errors, Some(0));
+
+        // Every transfer carries one flipped byte
+        let results = run_threaded(&params, &[("faults.corrupt", "1")]);
+        assert!(results.write_results[0].verify_errors.unwrap() > 0);
+        assert!(results.read_results[0].verify_errors.unwrap() > 0);
+    }
 }
@@ -0,0 +1,48 @@
+/// Per-rank bandwidth sampler: bytes completed in each fixed interval of a
+/// phase, indexed from the phase start.
+#[derive(Debug, Clone)]
+pub struct BandwidthSampler {
+    /// Interval length in seconds
+    interval: f64,
+    /// Phase start timestamp (`now()`)
+    start: f64,
+    /// Bytes completed per interval
+    bytes: Vec<i64>,
+}
+
+impl BandwidthSampler {
+    pub fn new(interval: f64, start: f64) -> Self {
+        Self {
+            interval,
+            start,
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Account `bytes` to the interval containing timestamp `t`.
+    pub fn record(&mut self, t: f64, bytes: i64) {
+        let idx = ((t - self.start).max(0.0) / self.interval) as usize;
+        if idx >= self.bytes.len() {
+            self.bytes.resize(idx + 1, 0);
+        }
+        self.bytes[idx] += bytes;
+    }
+
+    pub fn bytes(&self) -> &[i64] {
+        &self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_bins() {
+        let mut s = BandwidthSampler::new(0.1, 10.0);
+        s.record(10.05, 100);
+        s.record(10.09, 50);
+        s.record(10.35, 10);
+        assert_eq!(s.bytes(), &[150, 0, 0, 10]);
+    }
+}
@@ -0,0 +1,208 @@
+use ior_core::{AccessLayout, DataPacketType, IorParam};
+
+use crate::cli::{parse_duration, parse_size};
+
+/// Parse a C IOR script (`-f`) into one `IorParam` per `RUN`.
+///
+/// Settings are `key=value` pairs (several per line allowed), applied on top
+/// of `base` and inherited by every later `RUN`. A test that ends up with
+/// neither `writeFile` nor `readFile` does both, as on the command line:
+///
+/// ```text
+/// IOR START
+///     api=POSIX
+///     blockSize=64m transferSize=1m
+/// RUN
+///     transferSize=4m
+/// RUN
+/// IOR STOP
+/// ```
+///
+/// Reference: `parse_options.c:ReadConfigScript`
+pub fn parse_script(text: &str, base: &IorParam) -> Result<Vec<IorParam>, String> {
+    let mut current = base.clone();
+    let mut tests = Vec::new();
+    let mut started = false;
+    let mut stopped = false;
+
+    for (lineno, raw) in text.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let err = |msg: String| format!("script line {}: {}", lineno + 1, msg);
+
+        let upper = line.to_ascii_uppercase();
+        let words: Vec<&str> = upper.split_whitespace().collect();
+        match words.as_slice() {
+            ["IOR", "START"] => {
+                if started {
+                    return Err(err("duplicate IOR START".into()));
+                }
+                started = true;
+            }
+            ["IOR", "STOP"] => {
+                if !started {
+                    return Err(err("IOR STOP without IOR START".into()));
+                }
+                stopped = true;
+                break;
+            }
+            ["RUN"] => {
+                if !started {
+                    return Err(err("RUN outside IOR START/STOP".into()));
+                }
+                let mut test = current.clone();
+                if !test.write_file && !test.read_file {
+                    test.write_file = true;
+                    test.read_file = true;
+                }
+                tests.push(test);
+            }
+            _ => {
+                if !started {
+                    return Err(err(format!("setting outside IOR START/STOP: {}", line)));
+                }
+                for pair in line.split_whitespace() {
+                    let (key, value) = pair
+                        .split_once('=')
+                        .ok_or_else(|| err(format!("expected key=value, got '{}'", pair)))?;
+                    apply_setting(&mut current, key, value).map_err(err)?;
+                }
+            }
+        }
+    }
+
+    if !started || !stopped {
+        return Err("script must be enclosed in IOR START / IOR STOP".into());
+    }
+    if tests.is_empty() {
+        return Err("script contains no RUN".into());
+    }
+    Ok(tests)
+}
+
+/// Apply one script setting. Keys use C IOR's names, case-insensitively.
+///
+/// Reference: `parse_options.c:DecodeDirective`
+pub fn apply_setting(params: &mut IorParam, key: &str, value: &str) -> Result<(), String> {
+    let int = |v: &str| -> Result<i64, String> {
+        v.parse::<i64>().map_err(|_| format!("invalid number for {}: {}", key, v))
+    };
+    let flag = |v: &str| -> Result<bool, String> {
+        match v.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" => Ok(true),
+            "0" | "false" | "no" => Ok(false),
+            _ => Err(format!("invalid boolean for {}: {}", key, v)),
+        }
+    };
+
+    match key.to_ascii_lowercase().as_str() {
+        "api" => params.set_api(value),
+        "testfile" => params.set_test_file_name(value),
+        "blocksize" => params.block_size = parse_size(value).map_err(|e| e.to_string())?,
+        "transfersize" => {
+            params.transfer_size = parse_size(value).map_err(|e| e.to_string())?
+        }
+        "segmentcount" => params.segment_count = int(value)?,
+        "writefile" => params.write_file = flag(value)?,
+        "readfile" => params.read_file = flag(value)?,
+        "checkwrite" => params.check_write = flag(value)?,
+        "checkread" => params.check_read = flag(value)?,
+        "fileperproc" => params.file_per_proc = flag(value)?,
+        "randomoffset" => params.random_offset = flag(value)?,
+        "layout" => {
+            params.layout = AccessLayout::parse(&value.to_ascii_lowercase())
+                .ok_or_else(|| format!("invalid layout: {}", value))?
+        }
+        "repetitions" => params.repetitions = int(value)? as i32,
+        "intertestdelay" => params.inter_test_delay = int(value)? as i32,
+        "deadlineforstonewalling" => params.deadline_for_stonewalling = int(value)? as i32,
+        "stonewallingwearout" => params.stonewall_wear_out = flag(value)?,
+        "stonewallingwearoutiterations" => {
+            params.stonewall_wear_out_iterations = int(value)? as u64
+        }
+        "maxtimeduration" => params.max_time_duration = int(value)? as i32,
+        "mintimeduration" => params.min_time_duration = int(value)? as i32,
+        "sampleinterval" => {
+            params.sample_interval = parse_duration(value).map_err(|e| e.to_string())?
+        }
+        "verbose" => params.verbose = int(value)? as i32,
+        "keepfile" => params.keep_file = flag(value)?,
+        "fsync" => params.fsync = flag(value)?,
+        "fsyncperwrite" => params.fsync_per_write = flag(value)?,
+        "singlexferattempt" => params.single_xfer_attempt = flag(value)?,
+        "useexistingtestfile" => params.use_existing_test_file = flag(value)?,
+        "numtasks" => params.num_tasks = int(value)? as i32,
+        "reordertasks" | "reordertasksconstant" => params.reorder_tasks = flag(value)?,
+        "reordertasksrandom" => params.reorder_tasks_random = flag(value)?,
+        "taskpernodeoffset" => params.task_per_node_offset = int(value)? as i32,
+        "intratestbarriers" => params.intra_test_barriers = flag(value)?,
+        "queuedepth" => params.queue_depth = int(value)? as i32,
+        "directio" | "useo_direct" => params.direct_io = flag(value)?,
+        "datapackettype" => {
+            params.data_packet_type = DataPacketType::parse(value)
+                .ok_or_else(|| format!("invalid data packet type: {}", value))?
+        }
+        "timestampsignaturevalue" | "settimestampsignature" => {
+            params.time_stamp_signature_value = int(value)? as i32
+        }
+        _ => return Err(format!("unknown setting: {}", key)),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_inherit_settings() {
+        let script = "\
+# two transfer sizes, then a read-only run
+IOR START
+    api=POSIX
+    blockSize=4m transferSize=256k
+    writeFile=1 readFile=1
+RUN
+    transferSize=1m   # comment after a setting
+RUN
+    writeFile=0
+RUN
+IOR STOP
+";
+        let tests = parse_script(script, &IorParam::default()).unwrap();
+        assert_eq!(tests.len(), 3);
+        assert_eq!(tests[0].transfer_size, 262_144);
+        assert_eq!(tests[1].transfer_size, 1_048_576);
+        assert_eq!(tests[2].transfer_size, 1_048_576);
+        assert!(tests.iter().all(|t| t.block_size == 4 * 1_048_576));
+        assert!(tests[1].write_file);
+        assert!(!tests[2].write_file);
+        assert!(tests[2].read_file);
+    }
+
+    #[test]
+    fn test_base_params_are_inherited() {
+        let base = IorParam {
+            queue_depth: 8,
+            ..Default::default()
+        };
+        let tests = parse_script("IOR START\nRUN\nIOR STOP\n", &base).unwrap();
+        assert_eq!(tests[0].queue_depth, 8);
+        assert!(tests[0].write_file && tests[0].read_file);
+    }
+
+    #[test]
+    fn test_script_errors() {
+        let base = IorParam::default();
+        assert!(parse_script("IOR START\nbogus=1\nRUN\nIOR STOP\n", &base).is_err());
+        assert!(parse_script("IOR START\nblockSize\nRUN\nIOR STOP\n", &base).is_err());
+        assert!(parse_script("IOR START\nwriteFile=1\nIOR STOP\n", &base).is_err());
+        assert!(parse_script("RUN\n", &base).is_err());
+        let Err(err) = parse_script("IOR START\nRUN\nreadFile=maybe\nIOR STOP\n", &base) else {
+            panic!("invalid boolean accepted");
+        };
+        assert!(err.starts_with("script line 3"));
+    }
+}
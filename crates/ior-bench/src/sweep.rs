@@ -0,0 +1,107 @@
+use ior_core::IorParam;
+
+use crate::script::apply_setting;
+
+/// One swept parameter: a setting key and the values it takes.
+#[derive(Debug, Clone)]
+pub struct SweepAxis {
+    /// Key as given by the user (e.g. `queue_depth`)
+    pub key: String,
+    pub values: Vec<String>,
+}
+
+impl SweepAxis {
+    /// Parse a `--sweep` spec such as `queue_depth=1,2,4,8`.
+    ///
+    /// Keys are the script setting names; `_` and `-` are ignored, so
+    /// `queue_depth`, `queue-depth` and `queueDepth` are the same key.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (key, list) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("sweep must be key=v1,v2,...: {}", spec))?;
+        Self::new(key.trim(), list)
+    }
+
+    /// Build an axis from a comma-separated value list, checking that every
+    /// value applies cleanly.
+    pub fn new(key: &str, list: &str) -> Result<Self, String> {
+        let values: Vec<String> = list
+            .split(',')
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect();
+        if values.is_empty() {
+            return Err(format!("sweep over {} has no values", key));
+        }
+        let axis = Self {
+            key: key.to_string(),
+            values,
+        };
+        let mut probe = IorParam::default();
+        for value in &axis.values {
+            axis.apply(&mut probe, value)?;
+        }
+        Ok(axis)
+    }
+
+    fn apply(&self, params: &mut IorParam, value: &str) -> Result<(), String> {
+        let key: String = self.key.chars().filter(|c| *c != '_' && *c != '-').collect();
+        apply_setting(params, &key, value)
+    }
+}
+
+/// Expand `base` into the Cartesian product of `axes`, the first axis
+/// varying slowest. Each point carries the `(key, value)` pairs it was built
+/// from.
+pub fn expand(base: &IorParam, axes: &[SweepAxis]) -> Vec<(IorParam, Vec<(String, String)>)> {
+    let mut points = vec![(base.clone(), Vec::new())];
+    for axis in axes {
+        points = points
+            .into_iter()
+            .flat_map(|(params, labels)| {
+                axis.values.iter().map(move |value| {
+                    let mut point = params.clone();
+                    axis.apply(&mut point, value)
+                        .expect("sweep values are checked when the axis is built");
+                    let mut labels = labels.clone();
+                    labels.push((axis.key.clone(), value.clone()));
+                    (point, labels)
+                })
+            })
+            .collect();
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_cartesian() {
+        let axes = [
+            SweepAxis::new("transfer_size", "4k,1m").unwrap(),
+            SweepAxis::parse("queue_depth=1,2,4").unwrap(),
+        ];
+        let points = expand(&IorParam::default(), &axes);
+        assert_eq!(points.len(), 6);
+        assert_eq!(points[0].0.transfer_size, 4096);
+        assert_eq!(points[0].0.queue_depth, 1);
+        assert_eq!(points[2].0.queue_depth, 4);
+        assert_eq!(points[3].0.transfer_size, 1_048_576);
+        assert_eq!(points[3].0.queue_depth, 1);
+        assert_eq!(
+            points[5].1,
+            [("transfer_size".into(), "1m".into()), ("queue_depth".into(), "4".into())]
+        );
+    }
+
+    #[test]
+    fn test_invalid_axis() {
+        assert!(SweepAxis::parse("queue_depth").is_err());
+        assert!(SweepAxis::parse("bogus=1,2").is_err());
+        assert!(SweepAxis::parse("queue_depth=").is_err());
+        assert!(SweepAxis::parse("queue_depth=1,x").is_err());
+        assert!(SweepAxis::parse("num-tasks=1,2").is_ok());
+    }
+}
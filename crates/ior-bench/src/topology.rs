@@ -0,0 +1,66 @@
+use ior_core::Comm;
+
+/// Node placement of the ranks of a communicator. Each node is identified by
+/// the lowest rank running on it.
+///
+/// Reference: `utilities.c:GetNumNodes`, `GetNumTasksOnNode0`
+#[derive(Debug, Clone)]
+pub struct NodeMap {
+    node_of: Vec<i32>,
+}
+
+impl NodeMap {
+    /// Detect nodes with a shared-memory split of `comm` (collective).
+    pub fn detect(comm: &impl Comm) -> Self {
+        // The split keeps the original order, so local rank 0 is the lowest rank
+        let node_comm = comm.split_shared();
+        let mut leader = comm.rank();
+        node_comm.broadcast_value(0, &mut leader);
+
+        let mut node_of = vec![0i32; comm.size() as usize];
+        comm.all_gather(&[leader], &mut node_of);
+        Self { node_of }
+    }
+
+    /// Number of nodes hosting the first `num_tasks` ranks.
+    pub fn num_nodes(&self, num_tasks: i32) -> i32 {
+        let mut nodes = self.tasks(num_tasks).to_vec();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes.len() as i32
+    }
+
+    /// Number of the first `num_tasks` ranks that share a node with rank 0.
+    pub fn tasks_on_node0(&self, num_tasks: i32) -> i32 {
+        let tasks = self.tasks(num_tasks);
+        tasks.iter().filter(|&&node| node == tasks[0]).count() as i32
+    }
+
+    fn tasks(&self, num_tasks: i32) -> &[i32] {
+        &self.node_of[..(num_tasks as usize).min(self.node_of.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_for_task_subsets() {
+        // Two nodes of four ranks each, block placement
+        let map = NodeMap {
+            node_of: vec![0, 0, 0, 0, 4, 4, 4, 4],
+        };
+        assert_eq!(map.num_nodes(8), 2);
+        assert_eq!(map.tasks_on_node0(8), 4);
+        assert_eq!(map.num_nodes(3), 1);
+        assert_eq!(map.tasks_on_node0(3), 3);
+
+        // Round-robin placement
+        let map = NodeMap {
+            node_of: vec![0, 1, 0, 1, 0, 1],
+        };
+        assert_eq!(map.num_nodes(6), 2);
+        assert_eq!(map.tasks_on_node0(6), 3);
+    }
+}
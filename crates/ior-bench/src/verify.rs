@@ -0,0 +1,194 @@
+use ior_core::data_pattern::{self, DataPacketType, Mismatch, MismatchKind};
+use ior_core::{Comm, IorParam};
+
+use crate::runner::get_test_file_name;
+
+/// Mismatching words kept per rank and check; further errors are only counted.
+pub const MAX_MISMATCHES_PER_RANK: usize = 16;
+
+/// Words per packed mismatch: transfer offset, word, expected, actual, kind
+/// and the rank of an `OtherRank` kind.
+const RECORD_WORDS: usize = 6;
+
+/// Verification errors found by one rank during one check (`-W` or `-R`).
+#[derive(Debug, Default)]
+pub struct VerifyLog {
+    /// Mismatching words in total
+    pub errors: usize,
+    /// First mismatches with the file offset of their transfer
+    mismatches: Vec<(i64, Mismatch)>,
+}
+
+impl VerifyLog {
+    /// Verify one transfer buffer read from file `offset`.
+    pub fn verify(
+        &mut self,
+        offset: i64,
+        buf: &[u8],
+        seed: i32,
+        pretend_rank: i32,
+        data_type: DataPacketType,
+    ) {
+        let mut found = Vec::new();
+        self.errors += data_pattern::verify_pattern_detailed(
+            offset,
+            buf,
+            seed,
+            pretend_rank,
+            data_type,
+            MAX_MISMATCHES_PER_RANK - self.mismatches.len(),
+            &mut found,
+        );
+        self.mismatches.extend(found.into_iter().map(|m| (offset, m)));
+    }
+}
+
+/// One mismatching word, as gathered on rank 0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MismatchReport {
+    /// Rank that read the word
+    pub rank: i32,
+    /// Rank whose data was expected (differs from `rank` with -C/-Z)
+    pub expected_rank: i32,
+    pub file: String,
+    /// File offset of the transfer holding the word
+    pub transfer_offset: i64,
+    /// Word index within the transfer
+    pub word: usize,
+    pub expected: u64,
+    pub actual: u64,
+    pub kind: MismatchKind,
+}
+
+impl MismatchReport {
+    /// File offset of the mismatching word.
+    pub fn offset(&self) -> i64 {
+        self.transfer_offset + self.word as i64 * 8
+    }
+}
+
+/// Gather every rank's logged mismatches to rank 0 (collective). Other ranks
+/// get an empty list.
+pub fn gather_mismatches(
+    log: &VerifyLog,
+    params: &IorParam,
+    pretend_rank: i32,
+    comm: &impl Comm,
+) -> Vec<MismatchReport> {
+    let local = pack(log, pretend_rank);
+    let root_len = if comm.rank() == 0 {
+        local.len() * comm.size() as usize
+    } else {
+        0
+    };
+    let mut all = vec![0i64; root_len];
+    comm.gather(0, &local, &mut all);
+    all.chunks(local.len())
+        .enumerate()
+        .flat_map(|(rank, packed)| unpack(rank as i32, packed, params))
+        .collect()
+}
+
+/// Print gathered mismatches below a check's warning (rank 0 only).
+pub fn print_mismatches(mismatches: &[MismatchReport]) {
+    for m in mismatches {
+        eprintln!(
+            "  rank {} {} offset {} (transfer {} word {}): expected {:#018x} got {:#018x}, {}",
+            m.rank,
+            m.file,
+            m.offset(),
+            m.transfer_offset,
+            m.word,
+            m.expected,
+            m.actual,
+            m.kind,
+        );
+    }
+}
+
+/// Fixed-size encoding of a log for the gather: count, expected rank, then
+/// `MAX_MISMATCHES_PER_RANK` records (unused ones zero).
+fn pack(log: &VerifyLog, pretend_rank: i32) -> Vec<i64> {
+    let mut packed = vec![0i64; 2 + MAX_MISMATCHES_PER_RANK * RECORD_WORDS];
+    packed[0] = log.mismatches.len() as i64;
+    packed[1] = pretend_rank as i64;
+    for (record, (offset, m)) in packed[2..]
+        .chunks_mut(RECORD_WORDS)
+        .zip(&log.mismatches)
+    {
+        let (kind, other_rank) = match m.kind {
+            MismatchKind::Zero => (0, 0),
+            MismatchKind::OtherRank(r) => (1, r as i64),
+            MismatchKind::Stale => (2, 0),
+            MismatchKind::Unknown => (3, 0),
+        };
+        record.copy_from_slice(&[
+            *offset,
+            m.word as i64,
+            m.expected as i64,
+            m.actual as i64,
+            kind,
+            other_rank,
+        ]);
+    }
+    packed
+}
+
+fn unpack(rank: i32, packed: &[i64], params: &IorParam) -> Vec<MismatchReport> {
+    let count = packed[0] as usize;
+    let expected_rank = packed[1] as i32;
+    let file = get_test_file_name(params, expected_rank, 0);
+    packed[2..]
+        .chunks(RECORD_WORDS)
+        .take(count)
+        .map(|r| MismatchReport {
+            rank,
+            expected_rank,
+            file: file.clone(),
+            transfer_offset: r[0],
+            word: r[1] as usize,
+            expected: r[2] as u64,
+            actual: r[3] as u64,
+            kind: match r[4] {
+                0 => MismatchKind::Zero,
+                1 => MismatchKind::OtherRank(r[5] as i32),
+                2 => MismatchKind::Stale,
+                _ => MismatchKind::Unknown,
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_caps_and_roundtrips() {
+        let params = IorParam {
+            num_tasks: 4,
+            file_per_proc: true,
+            ..Default::default()
+        };
+        let data_type = DataPacketType::Timestamp;
+        let mut buf = vec![0u8; 4096];
+        data_pattern::generate_memory_pattern(&mut buf, 0, 2, data_type);
+        buf[..80].fill(0);
+        buf[4088..].copy_from_slice(&u64::MAX.to_ne_bytes());
+
+        let mut log = VerifyLog::default();
+        log.verify(8192, &buf, 0, 1, data_type);
+        log.verify(12288, &buf, 0, 1, data_type);
+        assert_eq!(log.errors, 2 * 512);
+        assert_eq!(log.mismatches.len(), MAX_MISMATCHES_PER_RANK);
+
+        let reports = unpack(3, &pack(&log, 1), &params);
+        assert_eq!(reports.len(), MAX_MISMATCHES_PER_RANK);
+        assert_eq!(reports[0].file, "testFile.00000001");
+        assert_eq!(reports[0].kind, MismatchKind::Zero);
+        assert_eq!(reports[10].kind, MismatchKind::OtherRank(2));
+        assert_eq!(reports[10].offset(), 8192 + 80);
+        assert_eq!(reports[15].expected, (1 << 32) | 15);
+        assert_eq!(reports[15].rank, 3);
+    }
+}
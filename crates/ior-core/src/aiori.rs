@@ -2,7 +2,9 @@ use std::cell::Cell;
 
 use crate::backend_options::BackendOptions;
 use crate::error::IorError;
-use crate::handle::{FileHandle, OpenFlags, StatResult, XferCallback, XferDir, XferResult, XferToken};
+use crate::handle::{
+    DirHandle, FileHandle, OpenFlags, StatResult, XferCallback, XferDir, XferResult, XferToken,
+};
 
 thread_local! {
     /// Per-thread monotonic counter for generating unique XferTokens.
@@ -73,6 +75,15 @@ pub trait Aiori {
     /// Cancel a pending async transfer.
     fn cancel(&self, token: XferToken) -> Result<(), IorError>;
 
+    /// Delete a file like [`delete`](Aiori::delete), but fail with
+    /// [`IorError::NotFound`] if it does not exist.
+    fn delete_strict(&self, path: &str) -> Result<(), IorError> {
+        if !self.access(path, libc::F_OK)? {
+            return Err(IorError::NotFound);
+        }
+        self.delete(path)
+    }
+
     /// Create a directory with given permissions.
     fn mkdir(&self, path: &str, mode: u32) -> Result<(), IorError> {
         let _ = (path, mode);
@@ -103,6 +114,31 @@ pub trait Aiori {
         Err(IorError::NotSupported)
     }
 
+    /// Open a directory for listing with [`readdir`](Aiori::readdir).
+    fn opendir(&self, path: &str) -> Result<DirHandle, IorError> {
+        let _ = path;
+        Err(IorError::NotSupported)
+    }
+
+    /// Next entry name of an open directory (without `.` and `..`), or
+    /// `None` at the end. Entries come in no particular order.
+    fn readdir(&self, dir: &mut DirHandle) -> Result<Option<String>, IorError> {
+        let _ = dir;
+        Err(IorError::NotSupported)
+    }
+
+    /// Close a directory opened with [`opendir`](Aiori::opendir).
+    fn closedir(&self, dir: DirHandle) -> Result<(), IorError> {
+        drop(dir);
+        Ok(())
+    }
+
+    /// Whether `xfer_sync` is collective over the test communicator, so every
+    /// rank must issue the same sequence of transfers.
+    fn collective_xfer(&self) -> bool {
+        false
+    }
+
     /// Configure backend-specific options parsed from `--prefix.key[=value]` arguments.
     ///
     /// Called once before benchmark execution. Default implementation does nothing.
@@ -41,7 +41,7 @@ impl OptionValue {
 }
 
 /// Collection of backend-specific options extracted from command-line arguments.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct BackendOptions {
     /// Stored as "prefix.key" → OptionValue.
     opts: BTreeMap<String, OptionValue>,
@@ -60,6 +60,11 @@ impl BackendOptions {
         self.opts.get(key)
     }
 
+    /// Add all options of `other`, replacing existing keys.
+    pub fn extend(&mut self, other: BackendOptions) {
+        self.opts.extend(other.opts);
+    }
+
     pub fn is_empty(&self) -> bool {
         self.opts.is_empty()
     }
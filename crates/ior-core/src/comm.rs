@@ -0,0 +1,394 @@
+//! Communicator abstraction for the benchmark drivers.
+//!
+//! [`Comm`] covers the collectives IOR and mdtest need. It is implemented for
+//! MPI's `SimpleCommunicator` (feature `mpi`) and by [`ThreadComm`], where
+//! every rank is a thread of one process, so benchmarks and tests of the full
+//! runners work without an MPI installation or `mpiexec`.
+
+use std::any::Any;
+use std::ops::Add;
+use std::sync::{Arc, Barrier, Mutex};
+
+/// Element-wise reduction operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReduceOp {
+    Sum,
+    Min,
+    Max,
+}
+
+impl ReduceOp {
+    fn apply<T: CommData>(self, a: T, b: T) -> T {
+        match self {
+            ReduceOp::Sum => a + b,
+            ReduceOp::Min => {
+                if b < a {
+                    b
+                } else {
+                    a
+                }
+            }
+            ReduceOp::Max => {
+                if b > a {
+                    b
+                } else {
+                    a
+                }
+            }
+        }
+    }
+}
+
+/// Plain numeric types that can be sent through a [`Comm`].
+#[cfg(feature = "mpi")]
+pub trait CommData:
+    Copy + Default + PartialOrd + Add<Output = Self> + Send + 'static + mpi::datatype::Equivalence
+{
+}
+
+/// Plain numeric types that can be sent through a [`Comm`].
+#[cfg(not(feature = "mpi"))]
+pub trait CommData: Copy + Default + PartialOrd + Add<Output = Self> + Send + 'static {}
+
+impl CommData for u8 {}
+impl CommData for i32 {}
+impl CommData for i64 {}
+impl CommData for u64 {}
+impl CommData for usize {}
+impl CommData for f64 {}
+
+/// Collective operations over a group of ranks. Every rank of the group must
+/// make the same calls in the same order; slices passed as `local` must have
+/// the same length on all ranks.
+pub trait Comm: Sized {
+    fn rank(&self) -> i32;
+
+    fn size(&self) -> i32;
+
+    fn barrier(&self);
+
+    /// Overwrite `buf` on every rank with its contents on `root`.
+    fn broadcast<T: CommData>(&self, root: i32, buf: &mut [T]);
+
+    /// Reduce `local` element-wise into `out` on `root`; `out` is left
+    /// untouched on the other ranks.
+    fn reduce<T: CommData>(&self, root: i32, local: &[T], out: &mut [T], op: ReduceOp);
+
+    /// Reduce `local` element-wise into `out` on every rank.
+    fn all_reduce<T: CommData>(&self, local: &[T], out: &mut [T], op: ReduceOp);
+
+    /// Concatenate every rank's `local`, in rank order, into `out` on `root`.
+    fn gather<T: CommData>(&self, root: i32, local: &[T], out: &mut [T]);
+
+    /// Concatenate every rank's `local`, in rank order, into `out` everywhere.
+    fn all_gather<T: CommData>(&self, local: &[T], out: &mut [T]);
+
+    /// Split into one communicator per `color`, ranked by `key` and then by
+    /// the current rank. Ranks passing `None` take part but get nothing back.
+    fn split(&self, color: Option<i32>, key: i32) -> Option<Self>;
+
+    /// Communicator of the ranks sharing this rank's node, in rank order.
+    fn split_shared(&self) -> Self;
+
+    /// Terminate every rank of the job, e.g. after an error on one rank that
+    /// the others would wait for at their next collective.
+    fn abort(&self, code: i32) -> !;
+
+    /// The underlying MPI communicator, for backends doing their own MPI I/O.
+    #[cfg(feature = "mpi")]
+    fn as_mpi(&self) -> Option<&mpi::topology::SimpleCommunicator> {
+        None
+    }
+
+    /// Broadcast a single value from `root`.
+    fn broadcast_value<T: CommData>(&self, root: i32, value: &mut T) {
+        self.broadcast(root, std::slice::from_mut(value));
+    }
+
+    /// Reduce a single value to `root`; other ranks get their own value back.
+    fn reduce_value<T: CommData>(&self, root: i32, value: T, op: ReduceOp) -> T {
+        let mut out = value;
+        self.reduce(root, &[value], std::slice::from_mut(&mut out), op);
+        out
+    }
+
+    /// Reduce a single value on every rank.
+    fn all_reduce_value<T: CommData>(&self, value: T, op: ReduceOp) -> T {
+        let mut out = T::default();
+        self.all_reduce(&[value], std::slice::from_mut(&mut out), op);
+        out
+    }
+}
+
+#[cfg(feature = "mpi")]
+mod mpi_comm {
+    use mpi::collective::SystemOperation;
+    use mpi::topology::{Color, SimpleCommunicator};
+    use mpi::traits::{Communicator, CommunicatorCollectives, Root};
+
+    use super::{Comm, CommData, ReduceOp};
+
+    fn system_op(op: ReduceOp) -> SystemOperation {
+        match op {
+            ReduceOp::Sum => SystemOperation::sum(),
+            ReduceOp::Min => SystemOperation::min(),
+            ReduceOp::Max => SystemOperation::max(),
+        }
+    }
+
+    impl Comm for SimpleCommunicator {
+        fn rank(&self) -> i32 {
+            Communicator::rank(self)
+        }
+
+        fn size(&self) -> i32 {
+            Communicator::size(self)
+        }
+
+        fn barrier(&self) {
+            CommunicatorCollectives::barrier(self);
+        }
+
+        fn broadcast<T: CommData>(&self, root: i32, buf: &mut [T]) {
+            self.process_at_rank(root).broadcast_into(buf);
+        }
+
+        fn reduce<T: CommData>(&self, root: i32, local: &[T], out: &mut [T], op: ReduceOp) {
+            let process = self.process_at_rank(root);
+            if Communicator::rank(self) == root {
+                process.reduce_into_root(local, out, system_op(op));
+            } else {
+                process.reduce_into(local, system_op(op));
+            }
+        }
+
+        fn all_reduce<T: CommData>(&self, local: &[T], out: &mut [T], op: ReduceOp) {
+            self.all_reduce_into(local, out, system_op(op));
+        }
+
+        fn gather<T: CommData>(&self, root: i32, local: &[T], out: &mut [T]) {
+            let process = self.process_at_rank(root);
+            if Communicator::rank(self) == root {
+                process.gather_into_root(local, out);
+            } else {
+                process.gather_into(local);
+            }
+        }
+
+        fn all_gather<T: CommData>(&self, local: &[T], out: &mut [T]) {
+            self.all_gather_into(local, out);
+        }
+
+        fn split(&self, color: Option<i32>, key: i32) -> Option<Self> {
+            let color = color.map_or_else(Color::undefined, Color::with_value);
+            self.split_by_color_with_key(color, key)
+        }
+
+        fn split_shared(&self) -> Self {
+            Communicator::split_shared(self, 0)
+        }
+
+        fn abort(&self, code: i32) -> ! {
+            Communicator::abort(self, code)
+        }
+
+        fn as_mpi(&self) -> Option<&SimpleCommunicator> {
+            Some(self)
+        }
+    }
+}
+
+/// State shared by the ranks of one [`ThreadComm`] group.
+struct Group {
+    barrier: Barrier,
+    /// One exchange slot per rank
+    slots: Vec<Mutex<Option<Box<dyn Any + Send>>>>,
+}
+
+impl Group {
+    fn new(size: usize) -> Arc<Self> {
+        Arc::new(Self {
+            barrier: Barrier::new(size),
+            slots: (0..size).map(|_| Mutex::new(None)).collect(),
+        })
+    }
+}
+
+/// Communicator whose ranks are threads of the current process.
+///
+/// Collectives are built on an all-to-all exchange through per-rank slots
+/// between two barriers. A rank that panics leaves the others blocked in
+/// their next collective.
+pub struct ThreadComm {
+    rank: i32,
+    group: Arc<Group>,
+}
+
+impl ThreadComm {
+    /// Run `f` on `size` threads, each given its rank's communicator, and
+    /// return the results in rank order.
+    pub fn run<R: Send>(size: usize, f: impl Fn(ThreadComm) -> R + Sync) -> Vec<R> {
+        let group = Group::new(size);
+        std::thread::scope(|s| {
+            let handles: Vec<_> = (0..size)
+                .map(|rank| {
+                    let comm = ThreadComm {
+                        rank: rank as i32,
+                        group: Arc::clone(&group),
+                    };
+                    let f = &f;
+                    s.spawn(move || f(comm))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("rank thread panicked"))
+                .collect()
+        })
+    }
+
+    /// Publish `value` and return every rank's value in rank order.
+    fn exchange<T: Clone + Send + 'static>(&self, value: T) -> Vec<T> {
+        *self.group.slots[self.rank as usize].lock().unwrap() = Some(Box::new(value));
+        self.group.barrier.wait();
+        let all = self
+            .group
+            .slots
+            .iter()
+            .map(|slot| {
+                let slot = slot.lock().unwrap();
+                let value = slot.as_ref().and_then(|v| v.downcast_ref::<T>());
+                value.expect("ranks disagree on the collective").clone()
+            })
+            .collect();
+        // Nobody may publish the next value before everyone has read this one
+        self.group.barrier.wait();
+        all
+    }
+}
+
+impl Comm for ThreadComm {
+    fn rank(&self) -> i32 {
+        self.rank
+    }
+
+    fn size(&self) -> i32 {
+        self.group.slots.len() as i32
+    }
+
+    fn barrier(&self) {
+        self.group.barrier.wait();
+    }
+
+    fn broadcast<T: CommData>(&self, root: i32, buf: &mut [T]) {
+        let all = self.exchange(buf.to_vec());
+        buf.copy_from_slice(&all[root as usize]);
+    }
+
+    fn reduce<T: CommData>(&self, root: i32, local: &[T], out: &mut [T], op: ReduceOp) {
+        let all = self.exchange(local.to_vec());
+        if self.rank == root {
+            reduce_into(&all, out, op);
+        }
+    }
+
+    fn all_reduce<T: CommData>(&self, local: &[T], out: &mut [T], op: ReduceOp) {
+        reduce_into(&self.exchange(local.to_vec()), out, op);
+    }
+
+    fn gather<T: CommData>(&self, root: i32, local: &[T], out: &mut [T]) {
+        let all = self.exchange(local.to_vec());
+        if self.rank == root {
+            out.copy_from_slice(&all.concat());
+        }
+    }
+
+    fn all_gather<T: CommData>(&self, local: &[T], out: &mut [T]) {
+        out.copy_from_slice(&self.exchange(local.to_vec()).concat());
+    }
+
+    fn split(&self, color: Option<i32>, key: i32) -> Option<Self> {
+        let requests = self.exchange((color, key));
+        let mut members: Vec<usize> = (0..requests.len())
+            .filter(|&r| color.is_some() && requests[r].0 == color)
+            .collect();
+        members.sort_by_key(|&r| (requests[r].1, r));
+
+        // The first member creates the new group; the others pick it up
+        let leader = members.first().copied();
+        let created = (leader == Some(self.rank as usize)).then(|| Group::new(members.len()));
+        let groups = self.exchange(created);
+
+        let group = groups[leader?].clone()?;
+        let rank = members.iter().position(|&r| r == self.rank as usize)? as i32;
+        Some(ThreadComm { rank, group })
+    }
+
+    fn split_shared(&self) -> Self {
+        // All threads share one node
+        self.split(Some(0), 0).expect("every rank has a color")
+    }
+
+    fn abort(&self, code: i32) -> ! {
+        // All ranks live in this process
+        std::process::exit(code)
+    }
+}
+
+fn reduce_into<T: CommData>(all: &[Vec<T>], out: &mut [T], op: ReduceOp) {
+    out.copy_from_slice(&all[0]);
+    for values in &all[1..] {
+        for (acc, &v) in out.iter_mut().zip(values) {
+            *acc = op.apply(*acc, v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_collectives() {
+        let results = ThreadComm::run(4, |comm| {
+            let rank = comm.rank();
+            let mut seed = if rank == 2 { 42i64 } else { 0 };
+            comm.broadcast_value(2, &mut seed);
+
+            let sum = comm.all_reduce_value(rank as u64 + 1, ReduceOp::Sum);
+            let max = comm.all_reduce_value(rank as f64 * 0.5, ReduceOp::Max);
+            let min = comm.reduce_value(0, [7, 3, 9, 5][rank as usize], ReduceOp::Min);
+
+            let mut gathered = [0i32; 8];
+            comm.gather(0, &[rank, rank * 10], &mut gathered);
+            (seed, sum, max, min, gathered)
+        });
+
+        assert!(results.iter().all(|r| r.0 == 42 && r.1 == 10 && r.2 == 1.5));
+        assert_eq!(results[0].3, 3);
+        assert_eq!(results[0].4, [0, 0, 1, 10, 2, 20, 3, 30]);
+        // Non-root ranks keep their own values
+        assert_eq!(results[1].3, 3);
+        assert_eq!(results[2].3, 9);
+        assert_eq!(results[2].4, [0; 8]);
+    }
+
+    #[test]
+    fn test_thread_split() {
+        let results = ThreadComm::run(5, |comm| {
+            let rank = comm.rank();
+            // Even ranks in reverse order, rank 3 excluded
+            let color = (rank != 3).then_some(rank % 2);
+            let sub = comm.split(color, -rank);
+            sub.map(|sub| {
+                let total = sub.all_reduce_value(rank, ReduceOp::Sum);
+                (sub.rank(), sub.size(), total)
+            })
+        });
+
+        assert_eq!(results[0], Some((2, 3, 6)));
+        assert_eq!(results[4], Some((0, 3, 6)));
+        assert_eq!(results[1], Some((0, 1, 1)));
+        assert_eq!(results[3], None);
+    }
+}
@@ -7,6 +7,20 @@ pub enum DataPacketType {
     Timestamp,
     /// Offset-based pattern: Timestamp + offset stamps at 512-word boundaries
     Offset,
+    /// Pseudo-random words seeded per (seed, rank, offset): neither
+    /// compressible nor dedupable, at the cost of regenerating every transfer
+    Random,
+    /// Pseudo-random words seeded per (seed, rank), generated once: defeats
+    /// compression but every transfer of a rank carries the same data
+    Incompressible,
+    /// Per-4 KiB-block data with a target compressibility and dedupe ratio
+    /// (fio's `buffer_compress_percentage` / `dedupe_percentage`)
+    Tunable {
+        /// Percentage of each block that is zero-filled (compressible)
+        compress: u8,
+        /// Percentage of blocks that are copies of one shared block
+        dedupe: u8,
+    },
 }
 
 impl Default for DataPacketType {
@@ -15,9 +29,86 @@ impl Default for DataPacketType {
     }
 }
 
+impl DataPacketType {
+    /// Parse a `-l` value (C IOR names and one-letter forms).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "timestamp" | "t" => Some(Self::Timestamp),
+            "offset" | "o" => Some(Self::Offset),
+            "random" | "r" => Some(Self::Random),
+            "incompressible" | "i" => Some(Self::Incompressible),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Timestamp => "timestamp",
+            Self::Offset => "offset",
+            Self::Random => "random",
+            Self::Incompressible => "incompressible",
+            Self::Tunable { .. } => "tunable",
+        }
+    }
+}
+
+/// Block size for `Tunable` compress/dedupe decisions (fio's default 4 KiB).
+pub const TUNABLE_BLOCK_SIZE: usize = 4096;
+
+/// Fill `buf`, which starts at file `offset`, with `Tunable` blocks. Each
+/// block is keyed by its absolute file offset: a `dedupe`% share of blocks
+/// carry one block shared by all ranks (keyed by the seed only), and every
+/// block is random for its first `100 - compress`% and zero after that.
+fn fill_tunable(buf: &mut [u8], seed: i32, pretend_rank: i32, offset: i64, compress: u8, dedupe: u8) {
+    let random_len = TUNABLE_BLOCK_SIZE * (100 - compress.min(100) as usize) / 100 / 8 * 8;
+    for (i, block) in buf.chunks_mut(TUNABLE_BLOCK_SIZE).enumerate() {
+        let block_offset = offset + (i * TUNABLE_BLOCK_SIZE) as i64;
+        let mut key = random_state(seed, pretend_rank, block_offset) ^ 0xA076_1D64_78BD_642F;
+        let duplicate = splitmix64(&mut key) % 100 < dedupe as u64;
+        let mut state = if duplicate {
+            random_state(seed, -1, -1)
+        } else {
+            random_state(seed, pretend_rank, block_offset)
+        };
+
+        let split = random_len.min(block.len());
+        let (random, zeros) = block.split_at_mut(split);
+        for word in random.chunks_mut(8) {
+            let bytes = splitmix64(&mut state).to_ne_bytes();
+            word.copy_from_slice(&bytes[..word.len()]);
+        }
+        zeros.fill(0);
+    }
+}
+
+/// splitmix64 step: advances `state` and returns the next output word.
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Initial PRNG state for a rank's stream; `offset` is 0 for Incompressible.
+fn random_state(seed: i32, pretend_rank: i32, offset: i64) -> u64 {
+    let mut state = ((pretend_rank as u64) << 32) | (seed as u32 as u64);
+    let mixed = splitmix64(&mut state);
+    mixed ^ (offset as u64).wrapping_mul(0xD6E8_FEB8_6659_FD93)
+}
+
+/// Fill `buf` with the PRNG stream for (seed, rank, offset).
+fn fill_random(buf: &mut [u8], seed: i32, pretend_rank: i32, offset: i64) {
+    let mut state = random_state(seed, pretend_rank, offset);
+    for word in buf.chunks_exact_mut(8) {
+        word.copy_from_slice(&splitmix64(&mut state).to_ne_bytes());
+    }
+}
+
 /// Generate the initial memory pattern for the write buffer.
 ///
-/// Fills the buffer with 64-bit words: `(pretend_rank << 32) | (seed + word_index)`.
+/// Fills the buffer with 64-bit words: `(pretend_rank << 32) | (seed + word_index)`,
+/// or with the rank's PRNG stream for `Random`/`Incompressible`.
 /// Called once at test start to set up the base pattern.
 ///
 /// Reference: C IOR `utilities.c:94-112` (`generate_memory_pattern`)
@@ -25,8 +116,18 @@ pub fn generate_memory_pattern(
     buf: &mut [u8],
     seed: i32,
     pretend_rank: i32,
-    _data_type: DataPacketType,
+    data_type: DataPacketType,
 ) {
+    match data_type {
+        DataPacketType::Random | DataPacketType::Incompressible => {
+            return fill_random(buf, seed, pretend_rank, 0);
+        }
+        DataPacketType::Tunable { compress, dedupe } => {
+            return fill_tunable(buf, seed, pretend_rank, 0, compress, dedupe);
+        }
+        DataPacketType::Timestamp | DataPacketType::Offset => {}
+    }
+
     let words = buf.len() / 8;
     let rank_hi = (pretend_rank as u64) << 32;
 
@@ -40,18 +141,25 @@ pub fn generate_memory_pattern(
 ///
 /// For `Offset` mode, stamps the transfer offset at every 512-word (4096-byte)
 /// boundary within the buffer: `(offset * stride) | (rank << 32)`.
-/// For `Timestamp` mode, this is a no-op since the base pattern is already per-rank unique.
+/// For `Random` and `Tunable` modes, regenerates the whole buffer for the offset.
+/// For `Timestamp` and `Incompressible` modes, this is a no-op since the base
+/// pattern is already per-rank unique.
 ///
 /// Reference: C IOR `utilities.c:115-144` (`update_write_memory_pattern`)
 pub fn update_write_pattern(
     offset: i64,
     buf: &mut [u8],
-    _seed: i32,
+    seed: i32,
     pretend_rank: i32,
     data_type: DataPacketType,
 ) {
-    if data_type != DataPacketType::Offset {
-        return;
+    match data_type {
+        DataPacketType::Offset => {}
+        DataPacketType::Random => return fill_random(buf, seed, pretend_rank, offset),
+        DataPacketType::Tunable { compress, dedupe } => {
+            return fill_tunable(buf, seed, pretend_rank, offset, compress, dedupe);
+        }
+        DataPacketType::Timestamp | DataPacketType::Incompressible => return,
     }
 
     let rank_hi = (pretend_rank as u64) << 32;
@@ -69,49 +177,164 @@ pub fn update_write_pattern(
     }
 }
 
-/// Verify the buffer against the expected pattern. Returns number of errors.
-///
-/// Regenerates the expected pattern and compares word-by-word.
-///
-/// Reference: C IOR `utilities.c:147-170`
-pub fn verify_pattern(
+/// What a mismatching word looks like, as a hint to the cause of the
+/// corruption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MismatchKind {
+    /// All-zero word: a hole or a block that was never written
+    Zero,
+    /// Another rank's stamp at the expected position: a misdirected write
+    OtherRank(i32),
+    /// This rank's stamp from another offset or seed: stale or shifted data
+    Stale,
+    /// Not recognisable (always the case for the pseudo-random patterns)
+    Unknown,
+}
+
+impl MismatchKind {
+    /// Classify `actual` found where `expected` was due. Only the timestamp
+    /// patterns carry the writer's rank in the upper 32 bits of every word.
+    pub fn classify(expected: u64, actual: u64, data_type: DataPacketType) -> Self {
+        if actual == 0 {
+            return Self::Zero;
+        }
+        match data_type {
+            DataPacketType::Timestamp | DataPacketType::Offset => {
+                if actual >> 32 == expected >> 32 {
+                    Self::Stale
+                } else if actual as u32 == expected as u32 {
+                    Self::OtherRank((actual >> 32) as i32)
+                } else {
+                    Self::Unknown
+                }
+            }
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl std::fmt::Display for MismatchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Zero => f.write_str("zeros (hole or unwritten block)"),
+            Self::OtherRank(rank) => write!(f, "rank {}'s data (misdirected write)", rank),
+            Self::Stale => f.write_str("own data from another offset (stale block)"),
+            Self::Unknown => f.write_str("unrecognised data"),
+        }
+    }
+}
+
+/// One mismatching 64-bit word of a verified transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Word index within the transfer buffer
+    pub word: usize,
+    pub expected: u64,
+    pub actual: u64,
+    pub kind: MismatchKind,
+}
+
+/// Native-endian word from up to 8 bytes (a short tail is zero-padded).
+fn word_at(bytes: &[u8]) -> u64 {
+    let mut word = [0u8; 8];
+    word[..bytes.len()].copy_from_slice(bytes);
+    u64::from_ne_bytes(word)
+}
+
+/// Compare `buf` word-by-word against the expected pattern, calling
+/// `on_mismatch(word, expected, actual)` for every difference. Returns the
+/// number of mismatching words.
+fn compare_pattern(
     offset: i64,
     buf: &[u8],
     seed: i32,
     pretend_rank: i32,
     data_type: DataPacketType,
+    mut on_mismatch: impl FnMut(usize, u64, u64),
 ) -> usize {
-    let words = buf.len() / 8;
-    let rank_hi = (pretend_rank as u64) << 32;
     let mut errors = 0;
+    let mut check = |i: usize, expected: u64, actual: u64| {
+        if actual != expected {
+            errors += 1;
+            on_mismatch(i, expected, actual);
+        }
+    };
 
-    // Check base timestamp pattern
-    for i in 0..words {
-        let actual = u64::from_ne_bytes(buf[i * 8..(i + 1) * 8].try_into().unwrap());
-        let expected = rank_hi | ((seed as u64).wrapping_add(i as u64) & 0xFFFF_FFFF);
-
-        // For Offset mode, some positions are overwritten with offset stamps
-        if data_type == DataPacketType::Offset {
-            let stride = 512;
-            if i % stride == 0 {
-                let k = (i / stride) as u64;
-                let expected_stamp =
-                    rank_hi | (((offset as u64).wrapping_mul(k.wrapping_add(1))) & 0xFFFF_FFFF);
-                if actual != expected_stamp {
-                    errors += 1;
-                }
-                continue;
+    match data_type {
+        DataPacketType::Random | DataPacketType::Incompressible => {
+            let stream_offset = if data_type == DataPacketType::Random { offset } else { 0 };
+            let mut state = random_state(seed, pretend_rank, stream_offset);
+            for (i, word) in buf.chunks_exact(8).enumerate() {
+                check(i, splitmix64(&mut state), word_at(word));
+            }
+        }
+        DataPacketType::Tunable { compress, dedupe } => {
+            let mut expected = vec![0u8; buf.len()];
+            fill_tunable(&mut expected, seed, pretend_rank, offset, compress, dedupe);
+            for (i, (actual, expected)) in buf.chunks(8).zip(expected.chunks(8)).enumerate() {
+                check(i, word_at(expected), word_at(actual));
             }
         }
+        DataPacketType::Timestamp | DataPacketType::Offset => {
+            let rank_hi = (pretend_rank as u64) << 32;
+            let stride = 512;
 
-        if actual != expected {
-            errors += 1;
+            for (i, word) in buf.chunks_exact(8).enumerate() {
+                // For Offset mode, some positions are overwritten with offset stamps
+                let expected = if data_type == DataPacketType::Offset && i % stride == 0 {
+                    let k = (i / stride) as u64;
+                    rank_hi | (((offset as u64).wrapping_mul(k.wrapping_add(1))) & 0xFFFF_FFFF)
+                } else {
+                    rank_hi | ((seed as u64).wrapping_add(i as u64) & 0xFFFF_FFFF)
+                };
+                check(i, expected, word_at(word));
+            }
         }
     }
 
     errors
 }
 
+/// Verify the buffer against the expected pattern. Returns number of errors.
+///
+/// Regenerates the expected pattern and compares word-by-word.
+///
+/// Reference: C IOR `utilities.c:147-170`
+pub fn verify_pattern(
+    offset: i64,
+    buf: &[u8],
+    seed: i32,
+    pretend_rank: i32,
+    data_type: DataPacketType,
+) -> usize {
+    compare_pattern(offset, buf, seed, pretend_rank, data_type, |_, _, _| {})
+}
+
+/// Like [`verify_pattern`], but also appends the first `limit` mismatching
+/// words, classified, to `found`.
+pub fn verify_pattern_detailed(
+    offset: i64,
+    buf: &[u8],
+    seed: i32,
+    pretend_rank: i32,
+    data_type: DataPacketType,
+    limit: usize,
+    found: &mut Vec<Mismatch>,
+) -> usize {
+    let mut kept = 0;
+    compare_pattern(offset, buf, seed, pretend_rank, data_type, |word, expected, actual| {
+        if kept < limit {
+            kept += 1;
+            found.push(Mismatch {
+                word,
+                expected,
+                actual,
+                kind: MismatchKind::classify(expected, actual, data_type),
+            });
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,6 +390,35 @@ mod tests {
         assert!(errors > 0);
     }
 
+    #[test]
+    fn test_mismatch_classification() {
+        let data_type = DataPacketType::Offset;
+        let mut buf = vec![0u8; 8192];
+        generate_memory_pattern(&mut buf, 1, 2, data_type);
+        update_write_pattern(4096, &mut buf, 1, 2, data_type);
+
+        // Word 1 from rank 3's buffer, word 3 zeroed, word 512 stamped for another offset
+        let mut other = vec![0u8; 8192];
+        generate_memory_pattern(&mut other, 1, 3, data_type);
+        buf[8..16].copy_from_slice(&other[8..16]);
+        buf[24..32].fill(0);
+        update_write_pattern(0, &mut buf[4096..], 1, 2, data_type);
+
+        let mut found = Vec::new();
+        let errors = verify_pattern_detailed(4096, &buf, 1, 2, data_type, 2, &mut found);
+        assert_eq!(errors, 3);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].word, 1);
+        assert_eq!(found[0].kind, MismatchKind::OtherRank(3));
+        assert_eq!(found[1].kind, MismatchKind::Zero);
+        assert_eq!(found[1].expected, (2 << 32) | 4);
+
+        let mut found = Vec::new();
+        verify_pattern_detailed(4096, &buf, 1, 2, data_type, 8, &mut found);
+        assert_eq!(found[2].word, 512);
+        assert_eq!(found[2].kind, MismatchKind::Stale);
+    }
+
     #[test]
     fn test_timestamp_no_update_needed() {
         let mut buf = vec![0u8; 4096];
@@ -178,4 +430,81 @@ mod tests {
         update_write_pattern(1024, &mut buf, seed, rank, DataPacketType::Timestamp);
         assert_eq!(buf, before, "Timestamp mode should not modify buffer in update");
     }
+
+    #[test]
+    fn test_random_roundtrip() {
+        let mut buf = vec![0u8; 8192];
+        let seed = 3;
+        let rank = 4;
+
+        generate_memory_pattern(&mut buf, seed, rank, DataPacketType::Random);
+        update_write_pattern(65536, &mut buf, seed, rank, DataPacketType::Random);
+        assert_eq!(verify_pattern(65536, &buf, seed, rank, DataPacketType::Random), 0);
+
+        // Data written for another offset or rank does not verify
+        assert!(verify_pattern(0, &buf, seed, rank, DataPacketType::Random) > 0);
+        assert!(verify_pattern(65536, &buf, seed, rank + 1, DataPacketType::Random) > 0);
+    }
+
+    #[test]
+    fn test_random_differs_per_offset() {
+        let mut buf0 = vec![0u8; 4096];
+        let mut buf1 = vec![0u8; 4096];
+        update_write_pattern(0, &mut buf0, 0, 0, DataPacketType::Random);
+        update_write_pattern(4096, &mut buf1, 0, 0, DataPacketType::Random);
+        assert_ne!(buf0, buf1);
+    }
+
+    #[test]
+    fn test_incompressible_roundtrip() {
+        let mut buf = vec![0u8; 4096];
+        generate_memory_pattern(&mut buf, 9, 1, DataPacketType::Incompressible);
+        let before = buf.clone();
+        update_write_pattern(8192, &mut buf, 9, 1, DataPacketType::Incompressible);
+        assert_eq!(buf, before);
+        assert_eq!(verify_pattern(8192, &buf, 9, 1, DataPacketType::Incompressible), 0);
+    }
+
+    #[test]
+    fn test_parse_packet_type() {
+        assert_eq!(DataPacketType::parse("offset"), Some(DataPacketType::Offset));
+        assert_eq!(DataPacketType::parse("r"), Some(DataPacketType::Random));
+        assert_eq!(DataPacketType::parse("incompressible"), Some(DataPacketType::Incompressible));
+        assert_eq!(DataPacketType::parse("bogus"), None);
+    }
+
+    #[test]
+    fn test_tunable_roundtrip() {
+        let data_type = DataPacketType::Tunable { compress: 50, dedupe: 30 };
+        let mut buf = vec![0u8; 64 * 1024];
+        update_write_pattern(1 << 20, &mut buf, 11, 2, data_type);
+        assert_eq!(verify_pattern(1 << 20, &buf, 11, 2, data_type), 0);
+        assert!(verify_pattern(0, &buf, 11, 2, data_type) > 0);
+
+        // Second half of every block is zero-filled
+        for block in buf.chunks(TUNABLE_BLOCK_SIZE) {
+            assert!(block[TUNABLE_BLOCK_SIZE / 2..].iter().all(|&b| b == 0));
+            assert!(block[..TUNABLE_BLOCK_SIZE / 2].iter().any(|&b| b != 0));
+        }
+    }
+
+    #[test]
+    fn test_tunable_dedupe_ratio() {
+        let data_type = DataPacketType::Tunable { compress: 0, dedupe: 40 };
+        let mut buf = vec![0u8; 1000 * TUNABLE_BLOCK_SIZE];
+        update_write_pattern(0, &mut buf, 0, 0, data_type);
+
+        let blocks: Vec<&[u8]> = buf.chunks(TUNABLE_BLOCK_SIZE).collect();
+        let mut unique = blocks.clone();
+        unique.sort();
+        unique.dedup();
+        let dup_share = 1.0 - unique.len() as f64 / blocks.len() as f64;
+        assert!((dup_share - 0.4).abs() < 0.06, "duplicate share {dup_share}");
+
+        // Duplicate blocks are shared across ranks
+        let mut other = vec![0u8; 1000 * TUNABLE_BLOCK_SIZE];
+        update_write_pattern(0, &mut other, 0, 5, data_type);
+        let dup = blocks.iter().find(|b| blocks.iter().filter(|o| o == b).count() > 1).unwrap();
+        assert!(other.chunks(TUNABLE_BLOCK_SIZE).any(|b| b == *dup));
+    }
 }
@@ -26,6 +26,10 @@ pub enum IorError {
     #[error("cancelled")]
     Cancelled,
 
+    /// Transfer moved fewer bytes than requested and was not retried
+    #[error("short transfer: {done} of {len} bytes")]
+    ShortTransfer { done: i64, len: i64 },
+
     /// Operation not supported by this backend
     #[error("not supported")]
     NotSupported,
@@ -52,3 +56,40 @@ impl From<io::Error> for IorError {
         }
     }
 }
+
+/// Invalid benchmark parameter, reported before any I/O starts.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ParamError {
+    /// Value that does not parse (e.g. a size with an unknown suffix)
+    #[error("invalid {name}: '{value}'")]
+    InvalidValue { name: &'static str, value: String },
+
+    /// Numeric parameter outside its allowed range
+    #[error("{name} must be {expected} (got {value})")]
+    OutOfRange {
+        name: &'static str,
+        value: i64,
+        expected: &'static str,
+    },
+
+    /// Duration parameter (in seconds) outside its allowed range
+    #[error("{name} must be {expected} (got {secs}s)")]
+    DurationOutOfRange {
+        name: &'static str,
+        secs: f64,
+        expected: &'static str,
+    },
+
+    /// Parameter that must be a multiple of another
+    #[error("{name} ({value}) must be a multiple of {of_name} ({of})")]
+    NotMultiple {
+        name: &'static str,
+        value: i64,
+        of_name: &'static str,
+        of: i64,
+    },
+
+    /// Parameters that cannot be used together
+    #[error("{0}")]
+    Conflict(String),
+}
@@ -0,0 +1,673 @@
+//! Fault injection in front of any backend.
+//!
+//! [`FaultInjector`] wraps a backend and, driven by `--faults.*` options,
+//! makes transfers short, fail with EIO, complete late or out of order, or
+//! silently corrupt a byte, so that the harness's error, retry and
+//! verification paths can be exercised on any file system.
+
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, Instant};
+
+use crate::aiori::{Aiori, next_xfer_token};
+use crate::backend_options::{BackendOptions, OptionValue};
+use crate::data_pattern::splitmix64;
+use crate::error::IorError;
+use crate::handle::{
+    DirHandle, FileHandle, OpenFlags, StatResult, XferCallback, XferDir, XferResult, XferToken,
+};
+
+/// Wrap `backend` in a [`FaultInjector`] if any `faults.*` option is given.
+pub fn with_faults(backend: Box<dyn Aiori>, options: &BackendOptions) -> Box<dyn Aiori> {
+    if options.has_prefix("faults") {
+        Box::new(FaultInjector::new(backend))
+    } else {
+        backend
+    }
+}
+
+/// Faults to inject; probabilities are per transfer (or per metadata call).
+#[derive(Debug, Clone, Default)]
+struct FaultConfig {
+    /// Transfer only part of the requested length
+    short: f64,
+    /// Fail with EIO
+    eio: f64,
+    /// Fail transfers covering any of these file offsets with EIO
+    eio_offsets: Vec<i64>,
+    /// Extra time before each completion
+    delay: Duration,
+    /// Deliver async completions in random order
+    reorder: bool,
+    /// Flip one byte of the data written or read
+    corrupt: f64,
+    /// Fail metadata operations (open, stat, mkdir, ...) with EIO
+    meta_error: f64,
+}
+
+fn probability(key: &str, value: &OptionValue) -> Result<f64, IorError> {
+    match value.as_str().and_then(|s| s.parse::<f64>().ok()) {
+        Some(p) if (0.0..=1.0).contains(&p) => Ok(p),
+        _ => {
+            eprintln!(
+                "ERROR: faults.{} must be a probability between 0 and 1",
+                key
+            );
+            Err(IorError::InvalidArgument)
+        }
+    }
+}
+
+/// A completion waiting to be delivered.
+struct Held {
+    result: XferResult,
+    callback: XferCallback,
+    ready: Instant,
+}
+
+/// State reached from completion callbacks; boxed so its address is stable.
+struct FaultState {
+    rng: Cell<u64>,
+    delay: Cell<Duration>,
+    held: RefCell<Vec<Held>>,
+}
+
+impl FaultState {
+    fn hold(&self, result: XferResult, callback: XferCallback) {
+        self.held.borrow_mut().push(Held {
+            result,
+            callback,
+            ready: Instant::now() + self.delay.get(),
+        });
+    }
+}
+
+/// One intercepted async transfer, passed to the wrapped backend as
+/// `user_data`.
+struct Intercept {
+    state: *const FaultState,
+    user_data: usize,
+    callback: XferCallback,
+    /// Corrupt the byte at this fraction of the data read
+    corrupt_read: Option<f64>,
+    buf: *mut u8,
+    /// Byte changed before a corrupted write, restored on completion
+    restore: Option<(usize, u8)>,
+}
+
+/// Flip every bit of `buf[index]` and return its previous value.
+fn flip_byte(buf: *mut u8, index: usize) -> u8 {
+    unsafe {
+        let byte = buf.add(index);
+        let original = *byte;
+        *byte = !original;
+        original
+    }
+}
+
+fn restore_byte(buf: *mut u8, index: usize, original: u8) {
+    unsafe { *buf.add(index) = original };
+}
+
+extern "C" fn intercept_callback(result: *const XferResult) {
+    unsafe {
+        let res = &*result;
+        let intercept = Box::from_raw(res.user_data as *mut Intercept);
+        if let Some((index, byte)) = intercept.restore {
+            restore_byte(intercept.buf, index, byte);
+        }
+        if let Some(at) = intercept.corrupt_read
+            && res.error == 0
+            && res.bytes_transferred > 0
+        {
+            flip_byte(intercept.buf, (at * res.bytes_transferred as f64) as usize);
+        }
+        let forwarded = XferResult {
+            user_data: intercept.user_data,
+            ..*res
+        };
+        (*intercept.state).hold(forwarded, intercept.callback);
+    }
+}
+
+/// Backend wrapper injecting the faults configured by `--faults.*`.
+///
+/// Faults are drawn from a PRNG seeded by `faults.seed`, so a run with the
+/// same options and transfers injects the same faults. Metadata operations
+/// only fail with `faults.meta_error`; everything else applies to transfers.
+pub struct FaultInjector {
+    inner: Box<dyn Aiori>,
+    config: FaultConfig,
+    state: Box<FaultState>,
+}
+
+impl FaultInjector {
+    pub fn new(inner: Box<dyn Aiori>) -> Self {
+        Self {
+            inner,
+            config: FaultConfig::default(),
+            state: Box::new(FaultState {
+                rng: Cell::new(0),
+                delay: Cell::new(Duration::ZERO),
+                held: RefCell::new(Vec::new()),
+            }),
+        }
+    }
+
+    fn next_random(&self) -> u64 {
+        let mut state = self.state.rng.get();
+        let value = splitmix64(&mut state);
+        self.state.rng.set(state);
+        value
+    }
+
+    /// Uniform in [0, 1).
+    fn uniform(&self) -> f64 {
+        (self.next_random() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn chance(&self, p: f64) -> bool {
+        p > 0.0 && self.uniform() < p
+    }
+
+    fn meta(&self) -> Result<(), IorError> {
+        if self.chance(self.config.meta_error) {
+            Err(IorError::Io(libc::EIO))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether a transfer of `len` bytes at `offset` fails with EIO.
+    fn fails(&self, len: i64, offset: i64) -> bool {
+        let covered = self
+            .config
+            .eio_offsets
+            .iter()
+            .any(|&o| o >= offset && o < offset + len);
+        covered || self.chance(self.config.eio)
+    }
+
+    /// Length actually passed to the wrapped backend.
+    fn transfer_len(&self, len: i64) -> i64 {
+        if len > 1 && self.chance(self.config.short) {
+            1 + (self.uniform() * (len - 1) as f64) as i64
+        } else {
+            len
+        }
+    }
+
+    fn wait(&self) {
+        if !self.config.delay.is_zero() {
+            std::thread::sleep(self.config.delay);
+        }
+    }
+}
+
+impl Aiori for FaultInjector {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn configure(&mut self, options: &BackendOptions) -> Result<(), IorError> {
+        let mut seed = 0u64;
+        for (key, value) in options.for_prefix("faults") {
+            match key {
+                "seed" => seed = value.as_i64()? as u64,
+                "short" => self.config.short = probability(key, value)?,
+                "eio" => self.config.eio = probability(key, value)?,
+                "eio_offset" => {
+                    let list = value.as_str().unwrap_or("");
+                    self.config.eio_offsets = list
+                        .split(',')
+                        .map(|s| {
+                            s.trim()
+                                .parse::<i64>()
+                                .map_err(|_| IorError::InvalidArgument)
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                "delay_us" => {
+                    self.config.delay = Duration::from_micros(value.as_i64()?.max(0) as u64)
+                }
+                "reorder" => self.config.reorder = value.as_bool(),
+                "corrupt" => self.config.corrupt = probability(key, value)?,
+                "meta_error" => self.config.meta_error = probability(key, value)?,
+                unknown => {
+                    eprintln!("WARNING: unknown faults option: faults.{}", unknown);
+                }
+            }
+        }
+        self.state.rng.set(seed);
+        self.state.delay.set(self.config.delay);
+        self.inner.configure(options)
+    }
+
+    fn create(&self, path: &str, flags: OpenFlags) -> Result<FileHandle, IorError> {
+        self.meta()?;
+        self.inner.create(path, flags)
+    }
+
+    fn open(&self, path: &str, flags: OpenFlags) -> Result<FileHandle, IorError> {
+        self.meta()?;
+        self.inner.open(path, flags)
+    }
+
+    fn close(&self, handle: FileHandle) -> Result<(), IorError> {
+        self.inner.close(handle)
+    }
+
+    fn delete(&self, path: &str) -> Result<(), IorError> {
+        self.meta()?;
+        self.inner.delete(path)
+    }
+
+    fn fsync(&self, handle: &FileHandle) -> Result<(), IorError> {
+        self.inner.fsync(handle)
+    }
+
+    fn get_file_size(&self, path: &str) -> Result<i64, IorError> {
+        self.inner.get_file_size(path)
+    }
+
+    fn access(&self, path: &str, mode: i32) -> Result<bool, IorError> {
+        self.inner.access(path, mode)
+    }
+
+    fn xfer_submit(
+        &self,
+        handle: &FileHandle,
+        dir: XferDir,
+        buf: *mut u8,
+        len: i64,
+        offset: i64,
+        user_data: usize,
+        callback: XferCallback,
+    ) -> Result<XferToken, IorError> {
+        if self.fails(len, offset) {
+            let token = next_xfer_token();
+            let result = XferResult {
+                token,
+                bytes_transferred: -1,
+                error: libc::EIO,
+                user_data,
+            };
+            self.state.hold(result, callback);
+            return Ok(token);
+        }
+
+        let len = self.transfer_len(len);
+        let corrupt = self.chance(self.config.corrupt);
+        let mut intercept = Box::new(Intercept {
+            state: &*self.state,
+            user_data,
+            callback,
+            corrupt_read: None,
+            buf,
+            restore: None,
+        });
+        if corrupt {
+            let at = self.uniform();
+            match dir {
+                XferDir::Read => intercept.corrupt_read = Some(at),
+                XferDir::Write => {
+                    let index = (at * len as f64) as usize;
+                    intercept.restore = Some((index, flip_byte(buf, index)));
+                }
+            }
+        }
+
+        let restore = intercept.restore;
+        let intercept = Box::into_raw(intercept);
+        let submitted = self.inner.xfer_submit(
+            handle,
+            dir,
+            buf,
+            len,
+            offset,
+            intercept as usize,
+            intercept_callback,
+        );
+        if submitted.is_err() {
+            drop(unsafe { Box::from_raw(intercept) });
+            if let Some((index, byte)) = restore {
+                restore_byte(buf, index, byte);
+            }
+        }
+        submitted
+    }
+
+    /// Poll the wrapped backend, then deliver held completions whose delay
+    /// has passed (in random order with `faults.reorder`).
+    fn poll(&self, max_completions: usize) -> Result<usize, IorError> {
+        self.inner.poll(max_completions)?;
+
+        let now = Instant::now();
+        let mut ready = {
+            let mut held = self.state.held.borrow_mut();
+            let (ready, waiting): (Vec<Held>, Vec<Held>) =
+                held.drain(..).partition(|h| h.ready <= now);
+            *held = waiting;
+            ready
+        };
+        if self.config.reorder {
+            for i in (1..ready.len()).rev() {
+                ready.swap(i, (self.next_random() % (i as u64 + 1)) as usize);
+            }
+        }
+        if ready.len() > max_completions {
+            self.state
+                .held
+                .borrow_mut()
+                .extend(ready.drain(max_completions..));
+        }
+
+        let count = ready.len();
+        for held in ready {
+            (held.callback)(&held.result);
+        }
+        Ok(count)
+    }
+
+    fn cancel(&self, token: XferToken) -> Result<(), IorError> {
+        // A held completion has not been delivered yet, so it can still be cancelled
+        let held = {
+            let mut held = self.state.held.borrow_mut();
+            let pos = held.iter().position(|h| h.result.token == token);
+            pos.map(|pos| held.remove(pos))
+        };
+        match held {
+            Some(held) => {
+                let result = XferResult {
+                    bytes_transferred: 0,
+                    error: libc::ECANCELED,
+                    ..held.result
+                };
+                (held.callback)(&result);
+                Ok(())
+            }
+            None => self.inner.cancel(token),
+        }
+    }
+
+    fn delete_strict(&self, path: &str) -> Result<(), IorError> {
+        self.meta()?;
+        self.inner.delete_strict(path)
+    }
+
+    fn mkdir(&self, path: &str, mode: u32) -> Result<(), IorError> {
+        self.meta()?;
+        self.inner.mkdir(path, mode)
+    }
+
+    fn rmdir(&self, path: &str) -> Result<(), IorError> {
+        self.meta()?;
+        self.inner.rmdir(path)
+    }
+
+    fn stat(&self, path: &str) -> Result<StatResult, IorError> {
+        self.meta()?;
+        self.inner.stat(path)
+    }
+
+    fn rename(&self, old_path: &str, new_path: &str) -> Result<(), IorError> {
+        self.meta()?;
+        self.inner.rename(old_path, new_path)
+    }
+
+    fn mknod(&self, path: &str) -> Result<(), IorError> {
+        self.meta()?;
+        self.inner.mknod(path)
+    }
+
+    fn opendir(&self, path: &str) -> Result<DirHandle, IorError> {
+        self.meta()?;
+        self.inner.opendir(path)
+    }
+
+    fn readdir(&self, dir: &mut DirHandle) -> Result<Option<String>, IorError> {
+        self.inner.readdir(dir)
+    }
+
+    fn closedir(&self, dir: DirHandle) -> Result<(), IorError> {
+        self.inner.closedir(dir)
+    }
+
+    fn collective_xfer(&self) -> bool {
+        self.inner.collective_xfer()
+    }
+
+    fn xfer_sync(
+        &self,
+        handle: &FileHandle,
+        dir: XferDir,
+        buf: *mut u8,
+        len: i64,
+        offset: i64,
+    ) -> Result<i64, IorError> {
+        self.wait();
+        if self.fails(len, offset) {
+            return Err(IorError::Io(libc::EIO));
+        }
+
+        let len = self.transfer_len(len);
+        if !self.chance(self.config.corrupt) {
+            return self.inner.xfer_sync(handle, dir, buf, len, offset);
+        }
+        let at = self.uniform();
+        match dir {
+            XferDir::Read => {
+                let n = self.inner.xfer_sync(handle, dir, buf, len, offset)?;
+                if n > 0 {
+                    flip_byte(buf, (at * n as f64) as usize);
+                }
+                Ok(n)
+            }
+            XferDir::Write => {
+                // Write a changed copy of one byte, then restore the caller's buffer
+                let index = (at * len as f64) as usize;
+                let original = flip_byte(buf, index);
+                let written = self.inner.xfer_sync(handle, dir, buf, len, offset);
+                restore_byte(buf, index, original);
+                written
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Backend that completes every transfer in full on the next poll and
+    /// remembers the last byte written.
+    #[derive(Default)]
+    struct Echo {
+        pending: RefCell<Vec<(XferResult, XferCallback)>>,
+        written: Cell<u8>,
+    }
+
+    impl Aiori for Echo {
+        fn name(&self) -> &str {
+            "ECHO"
+        }
+        fn create(&self, _: &str, _: OpenFlags) -> Result<FileHandle, IorError> {
+            Ok(FileHandle::new(()))
+        }
+        fn open(&self, _: &str, _: OpenFlags) -> Result<FileHandle, IorError> {
+            Ok(FileHandle::new(()))
+        }
+        fn close(&self, _: FileHandle) -> Result<(), IorError> {
+            Ok(())
+        }
+        fn delete(&self, _: &str) -> Result<(), IorError> {
+            Ok(())
+        }
+        fn fsync(&self, _: &FileHandle) -> Result<(), IorError> {
+            Ok(())
+        }
+        fn get_file_size(&self, _: &str) -> Result<i64, IorError> {
+            Ok(0)
+        }
+        fn access(&self, _: &str, _: i32) -> Result<bool, IorError> {
+            Ok(true)
+        }
+        fn xfer_submit(
+            &self,
+            _: &FileHandle,
+            dir: XferDir,
+            buf: *mut u8,
+            len: i64,
+            _: i64,
+            user_data: usize,
+            callback: XferCallback,
+        ) -> Result<XferToken, IorError> {
+            if dir == XferDir::Write {
+                self.written.set(unsafe { *buf });
+            }
+            let token = next_xfer_token();
+            let result = XferResult {
+                token,
+                bytes_transferred: len,
+                error: 0,
+                user_data,
+            };
+            self.pending.borrow_mut().push((result, callback));
+            Ok(token)
+        }
+        fn poll(&self, _: usize) -> Result<usize, IorError> {
+            let pending: Vec<_> = self.pending.borrow_mut().drain(..).collect();
+            for (result, callback) in &pending {
+                callback(result);
+            }
+            Ok(pending.len())
+        }
+        fn cancel(&self, _: XferToken) -> Result<(), IorError> {
+            Ok(())
+        }
+    }
+
+    fn injector(options: &[(&str, &str)]) -> FaultInjector {
+        let mut opts = BackendOptions::new();
+        for (key, value) in options {
+            opts.insert(
+                format!("faults.{}", key),
+                OptionValue::Str(value.to_string()),
+            );
+        }
+        let mut faults = FaultInjector::new(Box::new(Echo::default()));
+        faults.configure(&opts).unwrap();
+        faults
+    }
+
+    extern "C" fn record(result: *const XferResult) {
+        unsafe {
+            let res = &*result;
+            let seen = &*(res.user_data as *const RefCell<Vec<(i64, i32)>>);
+            seen.borrow_mut().push((res.bytes_transferred, res.error));
+        }
+    }
+
+    #[test]
+    fn test_invalid_probability_is_rejected() {
+        let mut opts = BackendOptions::new();
+        opts.insert("faults.short".into(), OptionValue::Str("1.5".into()));
+        let mut faults = FaultInjector::new(Box::new(Echo::default()));
+        assert!(faults.configure(&opts).is_err());
+
+        let plain = BackendOptions::new();
+        assert_eq!(
+            with_faults(Box::new(Echo::default()), &plain).name(),
+            "ECHO"
+        );
+    }
+
+    #[test]
+    fn test_sync_faults() {
+        let handle = FileHandle::new(());
+        let mut buf = vec![7u8; 4096];
+        let ptr = buf.as_mut_ptr();
+
+        let faults = injector(&[("short", "1")]);
+        let n = faults
+            .xfer_sync(&handle, XferDir::Read, ptr, 4096, 0)
+            .unwrap();
+        assert!(n > 0 && n < 4096);
+
+        let faults = injector(&[("eio_offset", "8192,100000")]);
+        assert!(
+            faults
+                .xfer_sync(&handle, XferDir::Read, ptr, 4096, 0)
+                .is_ok()
+        );
+        assert!(matches!(
+            faults.xfer_sync(&handle, XferDir::Read, ptr, 4096, 4096 + 4095),
+            Err(IorError::Io(libc::EIO))
+        ));
+
+        let faults = injector(&[("meta_error", "1")]);
+        assert!(faults.mkdir("/d", 0o755).is_err());
+        assert!(
+            faults
+                .xfer_sync(&handle, XferDir::Read, ptr, 4096, 0)
+                .is_ok()
+        );
+
+        // The corrupted byte reaches the backend, the caller's buffer is unchanged
+        let faults = injector(&[("corrupt", "1")]);
+        faults
+            .xfer_sync(&handle, XferDir::Write, ptr, 1, 0)
+            .unwrap();
+        assert_eq!(buf[0], 7);
+    }
+
+    #[test]
+    fn test_async_faults_are_delivered_on_poll() {
+        let handle = FileHandle::new(());
+        let mut buf = vec![0u8; 4096];
+        let seen: RefCell<Vec<(i64, i32)>> = RefCell::new(Vec::new());
+        let user_data = &seen as *const _ as usize;
+
+        let faults = injector(&[("eio", "0.5"), ("reorder", "1"), ("seed", "3")]);
+        for i in 0..32 {
+            faults
+                .xfer_submit(
+                    &handle,
+                    XferDir::Write,
+                    buf.as_mut_ptr(),
+                    4096,
+                    i * 4096,
+                    user_data,
+                    record,
+                )
+                .unwrap();
+        }
+        let mut delivered = 0;
+        while delivered < 32 {
+            delivered += faults.poll(8).unwrap();
+        }
+        let seen = seen.into_inner();
+        assert_eq!(seen.len(), 32);
+        let failed = seen.iter().filter(|&&(_, e)| e == libc::EIO).count();
+        assert!(failed > 0 && failed < 32);
+        assert!(seen.iter().all(|&(n, e)| e != 0 || n == 4096));
+
+        // An injected failure is held until polled, so it can be cancelled
+        let seen: RefCell<Vec<(i64, i32)>> = RefCell::new(Vec::new());
+        let user_data = &seen as *const _ as usize;
+        let faults = injector(&[("eio", "1")]);
+        let token = faults
+            .xfer_submit(
+                &handle,
+                XferDir::Read,
+                buf.as_mut_ptr(),
+                4096,
+                0,
+                user_data,
+                record,
+            )
+            .unwrap();
+        faults.cancel(token).unwrap();
+        assert_eq!(faults.poll(1).unwrap(), 0);
+        assert_eq!(seen.into_inner(), vec![(0, libc::ECANCELED)]);
+    }
+}
@@ -1,7 +1,11 @@
 use std::any::Any;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_void};
 
 use bitflags::bitflags;
 
+use crate::error::IorError;
+
 /// Opaque file handle wrapping backend-specific state.
 pub struct FileHandle {
     inner: Box<dyn Any + Send + Sync>,
@@ -21,6 +25,70 @@ impl FileHandle {
     }
 }
 
+/// Opaque directory stream returned by [`Aiori::opendir`](crate::Aiori::opendir).
+pub struct DirHandle {
+    inner: Box<dyn Any + Send>,
+}
+
+impl DirHandle {
+    /// Create a new directory handle from any backend-specific type.
+    pub fn new<T: Any + Send>(value: T) -> Self {
+        Self {
+            inner: Box::new(value),
+        }
+    }
+
+    /// Attempt to downcast to the concrete type.
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.inner.downcast_mut::<T>()
+    }
+
+    /// List a whole directory up front through a C `readdir` taking a
+    /// [`DirFiller`]: `list(buf, filler)` makes the call and returns its
+    /// result code, which is the error if nonzero. The names (without `.`
+    /// and `..`) are handed out by [`next_name`](Self::next_name).
+    pub fn from_filler(list: impl FnOnce(*mut c_void, DirFiller) -> c_int) -> Result<Self, c_int> {
+        let mut names: Vec<String> = Vec::new();
+        let rc = list(&mut names as *mut Vec<String> as *mut c_void, collect_name);
+        if rc != 0 {
+            return Err(rc);
+        }
+        Ok(Self::new(names.into_iter()))
+    }
+
+    /// Next entry name of a handle made by [`from_filler`](Self::from_filler).
+    pub fn next_name(&mut self) -> Result<Option<String>, IorError> {
+        let names = self
+            .downcast_mut::<std::vec::IntoIter<String>>()
+            .ok_or(IorError::InvalidArgument)?;
+        Ok(names.next())
+    }
+}
+
+/// Entry callback of FUSE-style C `readdir` calls (CHFS, BenchFS): receives
+/// the `buf` given to `readdir`, the entry name, its stat and offset, and
+/// returns nonzero to stop the listing.
+pub type DirFiller = unsafe extern "C" fn(
+    buf: *mut c_void,
+    name: *const c_char,
+    st: *const libc::stat,
+    off: libc::off_t,
+) -> c_int;
+
+/// [`DirFiller`] appending the entry name to the `Vec<String>` in `buf`.
+unsafe extern "C" fn collect_name(
+    buf: *mut c_void,
+    name: *const c_char,
+    _st: *const libc::stat,
+    _off: libc::off_t,
+) -> c_int {
+    let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+    if name != "." && name != ".." {
+        unsafe { (*(buf as *mut Vec<String>)).push(name.into_owned()) };
+    }
+    0
+}
+
 /// Monotonic token identifying an async transfer operation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct XferToken(pub u64);
@@ -86,3 +154,25 @@ bitflags! {
         const DIRECT  = 0x80;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir_handle_from_filler() {
+        let mut dir = DirHandle::from_filler(|buf, filler| {
+            for name in [c".", c"..", c"a", c"b"] {
+                unsafe { filler(buf, name.as_ptr(), std::ptr::null(), 0) };
+            }
+            0
+        })
+        .unwrap();
+        assert_eq!(dir.next_name().unwrap().as_deref(), Some("a"));
+        assert_eq!(dir.next_name().unwrap().as_deref(), Some("b"));
+        assert_eq!(dir.next_name().unwrap(), None);
+
+        assert_eq!(DirHandle::from_filler(|_, _| -1).err(), Some(-1));
+        assert!(DirHandle::new(0u8).next_name().is_err());
+    }
+}
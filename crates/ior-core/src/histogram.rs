@@ -0,0 +1,185 @@
+//! Log-bucketed latency histogram (HDR-style).
+//!
+//! Values are recorded in nanoseconds. Each power-of-two range is split into
+//! `SUB_BUCKETS / 2` linear sub-buckets, so any recorded value is reported
+//! within ~1.6% of its true value while the whole `u64` range fits in a fixed
+//! array of counts. Histograms with the same layout merge by adding counts,
+//! which is what the MPI reduction relies on.
+
+/// Sub-buckets in the first (linear) range; a power of two.
+const SUB_BUCKET_BITS: u32 = 7;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+const HALF_SUB_BUCKETS: u64 = SUB_BUCKETS / 2;
+
+/// Number of buckets needed to cover `0..=u64::MAX`.
+pub const HISTOGRAM_BUCKETS: usize =
+    ((64 - SUB_BUCKET_BITS as usize + 1) * HALF_SUB_BUCKETS as usize) + HALF_SUB_BUCKETS as usize;
+
+const NANOS_PER_SEC: f64 = 1e9;
+
+/// Per-rank latency histogram; one instance per I/O phase.
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    counts: Vec<u64>,
+    total: u64,
+    min: u64,
+    max: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            counts: vec![0; HISTOGRAM_BUCKETS],
+            total: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    /// Rebuild a histogram from merged parts (e.g. after an MPI reduction).
+    pub fn from_parts(counts: Vec<u64>, min: u64, max: u64) -> Self {
+        assert_eq!(counts.len(), HISTOGRAM_BUCKETS);
+        let total = counts.iter().sum();
+        Self { counts, total, min, max }
+    }
+
+    /// Record one latency sample given in seconds.
+    pub fn record_secs(&mut self, secs: f64) {
+        self.record((secs.max(0.0) * NANOS_PER_SEC) as u64);
+    }
+
+    /// Record one latency sample given in nanoseconds.
+    pub fn record(&mut self, nanos: u64) {
+        self.counts[bucket_index(nanos)] += 1;
+        self.total += 1;
+        self.min = self.min.min(nanos);
+        self.max = self.max.max(nanos);
+    }
+
+    /// Add all samples of `other` into `self`.
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        for (a, b) in self.counts.iter_mut().zip(&other.counts) {
+            *a += b;
+        }
+        self.total += other.total;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    pub fn count(&self) -> u64 {
+        self.total
+    }
+
+    /// Smallest recorded value in nanoseconds (`u64::MAX` if empty).
+    pub fn min(&self) -> u64 {
+        self.min
+    }
+
+    /// Largest recorded value in nanoseconds (0 if empty).
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// Value at percentile `p` (0-100) in nanoseconds: the upper edge of the
+    /// bucket holding that sample, capped at the recorded maximum.
+    pub fn value_at_percentile(&self, p: f64) -> u64 {
+        if self.total == 0 {
+            return 0;
+        }
+        let rank = ((p / 100.0) * self.total as f64).ceil().max(1.0) as u64;
+        let mut seen = 0u64;
+        for (idx, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return bucket_upper(idx).min(self.max).max(self.min);
+            }
+        }
+        self.max
+    }
+
+    /// Value at percentile `p` (0-100) in seconds.
+    pub fn percentile_secs(&self, p: f64) -> f64 {
+        self.value_at_percentile(p) as f64 / NANOS_PER_SEC
+    }
+}
+
+/// Bucket holding `value`. Values below `SUB_BUCKETS` map one-to-one; above
+/// that, each power of two gets `HALF_SUB_BUCKETS` buckets.
+fn bucket_index(value: u64) -> usize {
+    if value < SUB_BUCKETS {
+        return value as usize;
+    }
+    let msb = 63 - value.leading_zeros();
+    let shift = msb - (SUB_BUCKET_BITS - 1);
+    (shift as u64 * HALF_SUB_BUCKETS + (value >> shift)) as usize
+}
+
+/// Largest value that maps to bucket `idx`.
+fn bucket_upper(idx: usize) -> u64 {
+    let idx = idx as u64;
+    if idx < SUB_BUCKETS {
+        return idx;
+    }
+    let shift = idx / HALF_SUB_BUCKETS - 1;
+    let sub = idx % HALF_SUB_BUCKETS + HALF_SUB_BUCKETS;
+    (((sub as u128 + 1) << shift) - 1) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_bounds() {
+        for v in [0, 1, 127, 128, 129, 255, 256, 1_000_000, u64::MAX / 3, u64::MAX] {
+            let idx = bucket_index(v);
+            assert!(idx < HISTOGRAM_BUCKETS);
+            assert!(bucket_upper(idx) >= v);
+            // Relative error bounded by the sub-bucket width
+            let err = (bucket_upper(idx) - v) as f64;
+            assert!(err <= v as f64 / HALF_SUB_BUCKETS as f64);
+        }
+        assert_eq!(bucket_index(u64::MAX), HISTOGRAM_BUCKETS - 1);
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut h = LatencyHistogram::new();
+        for us in 1..=1000u64 {
+            h.record(us * 1000);
+        }
+        assert_eq!(h.count(), 1000);
+        let p50 = h.value_at_percentile(50.0) as f64;
+        assert!((p50 - 500_000.0).abs() / 500_000.0 < 0.02);
+        let p99 = h.value_at_percentile(99.0) as f64;
+        assert!((p99 - 990_000.0).abs() / 990_000.0 < 0.02);
+        assert_eq!(h.value_at_percentile(100.0), 1_000_000);
+        assert_eq!(LatencyHistogram::new().value_at_percentile(99.0), 0);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = LatencyHistogram::new();
+        let mut b = LatencyHistogram::new();
+        a.record_secs(0.001);
+        b.record_secs(0.5);
+        b.record_secs(0.002);
+        a.merge(&b);
+        assert_eq!(a.count(), 3);
+        assert_eq!(a.max(), 500_000_000);
+
+        let rebuilt = LatencyHistogram::from_parts(a.counts().to_vec(), a.min(), a.max());
+        assert_eq!(rebuilt.count(), 3);
+        assert_eq!(rebuilt.value_at_percentile(50.0), a.value_at_percentile(50.0));
+    }
+}
@@ -1,19 +1,31 @@
 pub mod aiori;
 pub mod aligned_buf;
 pub mod backend_options;
+pub mod comm;
 pub mod data_pattern;
 pub mod error;
+pub mod faults;
 pub mod ffi;
 pub mod handle;
+pub mod histogram;
 pub mod params;
 pub mod timer;
+#[cfg(feature = "workload")]
+pub mod workload;
 
 // Re-export primary types for convenience
 pub use aiori::Aiori;
 pub use aligned_buf::AlignedBuffer;
 pub use backend_options::{BackendOptions, OptionValue, extract_backend_options};
-pub use data_pattern::DataPacketType;
-pub use error::IorError;
-pub use handle::{FileHandle, OpenFlags, StatResult, XferCallback, XferDir, XferResult, XferToken};
-pub use params::IorParam;
+pub use comm::{Comm, ReduceOp, ThreadComm};
+pub use data_pattern::{DataPacketType, Mismatch, MismatchKind};
+pub use error::{IorError, ParamError};
+pub use faults::{FaultInjector, with_faults};
+pub use handle::{
+    DirHandle, FileHandle, OpenFlags, StatResult, XferCallback, XferDir, XferResult, XferToken,
+};
+pub use histogram::LatencyHistogram;
+pub use params::{AccessLayout, IorParam};
 pub use timer::{BenchTimers, now};
+#[cfg(feature = "workload")]
+pub use workload::Workload;
@@ -1,14 +1,50 @@
 use crate::data_pattern::DataPacketType;
+use crate::error::ParamError;
 
 /// Maximum length for test file name
 pub const MAX_STR: usize = 1024;
 /// Maximum length for API name
 pub const MAX_API: usize = 64;
+/// Offset and length alignment required for O_DIRECT (logical block size)
+pub const DIRECT_IO_ALIGNMENT: i64 = 512;
+/// Shortest bandwidth sample interval in seconds (one counter per interval)
+pub const MIN_SAMPLE_INTERVAL: f64 = 1e-3;
+
+/// How the ranks' transfers are laid out within each segment of a shared
+/// file. File-per-process runs are unaffected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccessLayout {
+    /// Each rank owns one contiguous `block_size` region per segment (C IOR)
+    #[default]
+    Segmented,
+    /// Consecutive transfers of different ranks interleave at `transfer_size`
+    /// granularity (strided N-1, as written by many checkpoint libraries)
+    Strided,
+}
+
+impl AccessLayout {
+    /// Parse a `--layout` value.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "segmented" => Some(Self::Segmented),
+            "strided" => Some(Self::Strided),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Segmented => "segmented",
+            Self::Strided => "strided",
+        }
+    }
+}
 
 /// IOR benchmark parameters, matching C IOR's `IOR_param_t`.
 ///
 /// Reference: `ior.h:77-167`, defaults from `ior.c:301-337`
 #[repr(C)]
+#[derive(Clone)]
 pub struct IorParam {
     // --- Transfer ---
     /// Contiguous bytes to write per task (default: 1 MiB)
@@ -27,6 +63,8 @@ pub struct IorParam {
     pub file_per_proc: bool,
     /// Use random offsets instead of sequential
     pub random_offset: bool,
+    /// Placement of the ranks' transfers in a shared file
+    pub layout: AccessLayout,
     /// Verify data after write
     pub check_write: bool,
     /// Verify data after read
@@ -49,6 +87,8 @@ pub struct IorParam {
     pub stonewall_wear_out: bool,
     /// Iteration count for stonewalling wear-out
     pub stonewall_wear_out_iterations: u64,
+    /// Bandwidth sampling interval in seconds (0 = disabled)
+    pub sample_interval: f64,
 
     // --- I/O behavior ---
     /// Verbosity level (0-5)
@@ -73,11 +113,11 @@ pub struct IorParam {
     // --- MPI ---
     /// Number of tasks (-1 = from MPI)
     pub num_tasks: i32,
-    /// Number of nodes (-1 = from MPI)
+    /// Number of nodes hosting the tasks (-1 = not yet detected)
     pub num_nodes: i32,
-    /// Tasks on node 0 (-1 = auto-detect)
+    /// Tasks on node 0, used as tasks per node (-1 = not yet detected)
     pub num_tasks_on_node0: i32,
-    /// Task offset for reorder reads (default: 1)
+    /// Nodes to shift by for `reorder_tasks` (default: 1)
     pub task_per_node_offset: i32,
     /// Reorder tasks for read-back (deterministic shift)
     pub reorder_tasks: bool,
@@ -122,6 +162,7 @@ impl Default for IorParam {
             read_file: false,
             file_per_proc: false,
             random_offset: false,
+            layout: AccessLayout::Segmented,
             check_write: false,
             check_read: false,
             random_seed: -1,
@@ -133,6 +174,7 @@ impl Default for IorParam {
             min_time_duration: 0,
             stonewall_wear_out: false,
             stonewall_wear_out_iterations: 0,
+            sample_interval: 0.0,
 
             verbose: 0,
             keep_file: false,
@@ -199,6 +241,94 @@ impl IorParam {
         self.api[..len].copy_from_slice(&bytes[..len]);
     }
 
+    /// Check the parameters for values the benchmark cannot run with and
+    /// return every problem found (empty if the test is runnable).
+    ///
+    /// `num_tasks` may still be -1 ("all ranks").
+    pub fn validate(&self) -> Vec<ParamError> {
+        let mut errors = Vec::new();
+        let mut at_least = |name, value: i64, min: i64, expected| {
+            if value < min {
+                errors.push(ParamError::OutOfRange {
+                    name,
+                    value,
+                    expected,
+                });
+            }
+        };
+        at_least("block size", self.block_size, 1, "positive");
+        at_least("transfer size", self.transfer_size, 1, "positive");
+        at_least("segment count", self.segment_count, 1, "at least 1");
+        at_least("queue depth", self.queue_depth as i64, 1, "at least 1");
+        at_least("repetitions", self.repetitions as i64, 1, "at least 1");
+        at_least("inter-test delay", self.inter_test_delay as i64, 0, "non-negative");
+        at_least(
+            "stonewalling deadline",
+            self.deadline_for_stonewalling as i64,
+            0,
+            "non-negative",
+        );
+        at_least("max time duration", self.max_time_duration as i64, 0, "non-negative");
+        at_least(
+            "task per node offset",
+            self.task_per_node_offset as i64,
+            1,
+            "at least 1",
+        );
+        if self.num_tasks == 0 || self.num_tasks < -1 {
+            at_least("number of tasks", self.num_tasks as i64, 1, "at least 1");
+        }
+        // 0 disables sampling
+        if self.sample_interval != 0.0 && self.sample_interval < MIN_SAMPLE_INTERVAL {
+            errors.push(ParamError::DurationOutOfRange {
+                name: "sample interval",
+                secs: self.sample_interval,
+                expected: "at least 1ms",
+            });
+        }
+
+        if self.block_size > 0 && self.transfer_size > 0 {
+            if self.block_size % self.transfer_size != 0 {
+                errors.push(ParamError::NotMultiple {
+                    name: "block size",
+                    value: self.block_size,
+                    of_name: "transfer size",
+                    of: self.transfer_size,
+                });
+            }
+            if self.direct_io && self.transfer_size % DIRECT_IO_ALIGNMENT != 0 {
+                errors.push(ParamError::NotMultiple {
+                    name: "transfer size",
+                    value: self.transfer_size,
+                    of_name: "the O_DIRECT alignment",
+                    of: DIRECT_IO_ALIGNMENT,
+                });
+            }
+        }
+
+        if !self.write_file && !self.read_file {
+            errors.push(ParamError::Conflict(
+                "test performs neither a write nor a read phase".into(),
+            ));
+        }
+        if self.check_read && !self.read_file {
+            errors.push(ParamError::Conflict(
+                "read verification (-R) requires the read phase (-r)".into(),
+            ));
+        }
+        if self.check_write && !self.write_file {
+            errors.push(ParamError::Conflict(
+                "write verification (-W) requires the write phase (-w)".into(),
+            ));
+        }
+        if self.api_str() == "MPIIO" && self.queue_depth > 1 {
+            errors.push(ParamError::Conflict(
+                "MPIIO has no asynchronous transfers; use queue depth 1 (-q)".into(),
+            ));
+        }
+        errors
+    }
+
     /// Calculate expected aggregate file size.
     /// Reference: `ior.c` expected file size calculation
     pub fn expected_agg_file_size(&self) -> i64 {
@@ -209,3 +339,73 @@ impl IorParam {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_valid() {
+        let params = IorParam {
+            write_file: true,
+            ..Default::default()
+        };
+        assert_eq!(params.validate(), []);
+    }
+
+    #[test]
+    fn test_validate_collects_all_problems() {
+        let params = IorParam {
+            block_size: 1_000_000,
+            transfer_size: 1000,
+            queue_depth: 0,
+            direct_io: true,
+            write_file: true,
+            check_read: true,
+            ..Default::default()
+        };
+        let errors = params.validate();
+        assert_eq!(errors.len(), 3);
+        assert!(errors.contains(&ParamError::NotMultiple {
+            name: "transfer size",
+            value: 1000,
+            of_name: "the O_DIRECT alignment",
+            of: DIRECT_IO_ALIGNMENT,
+        }));
+        assert_eq!(errors[0].to_string(), "queue depth must be at least 1 (got 0)");
+
+        let params = IorParam {
+            block_size: 1_048_576,
+            transfer_size: 300_000,
+            write_file: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            params.validate()[0].to_string(),
+            "block size (1048576) must be a multiple of transfer size (300000)"
+        );
+
+        let params = IorParam {
+            sample_interval: 1e-9,
+            write_file: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            params.validate()[0].to_string(),
+            "sample interval must be at least 1ms (got 0.000000001s)"
+        );
+
+        let mut params = IorParam {
+            queue_depth: 4,
+            write_file: true,
+            ..Default::default()
+        };
+        params.set_api("MPIIO");
+        assert_eq!(
+            params.validate(),
+            vec![ParamError::Conflict(
+                "MPIIO has no asynchronous transfers; use queue depth 1 (-q)".into()
+            )]
+        );
+    }
+}
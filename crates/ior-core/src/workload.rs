@@ -0,0 +1,429 @@
+//! Declarative workload files (`--config`).
+//!
+//! A workload file is TOML (or YAML, chosen by a `.yaml`/`.yml` extension)
+//! whose top-level keys are the long option names of the binary, a `backend`
+//! table holding `prefix.key` backend options, and an optional list of `test`
+//! tables whose keys override the top level for one test each:
+//!
+//! ```toml
+//! api = "POSIX"
+//! block-size = "64m"      # size suffixes as on the command line
+//! write-file = true
+//!
+//! [backend.posix]
+//! engine = "uring"
+//!
+//! [[test]]
+//! transfer-size = "1m"
+//!
+//! [[test]]
+//! transfer-size = "4m"
+//! backend.posix.engine = "threads"
+//! ```
+//!
+//! Settings are turned back into command-line arguments placed before the
+//! real ones, so explicit flags always take precedence over the file. A flag
+//! the file turns on is turned off again with its negation (see [`command`]).
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use clap::{Arg, ArgAction, Command, Parser};
+
+use crate::backend_options::{BackendOptions, OptionValue};
+
+/// Scalar value of one workload setting.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Setting {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Setting::Bool(b) => write!(f, "{}", b),
+            Setting::Int(n) => write!(f, "{}", n),
+            Setting::Float(x) => write!(f, "{}", x),
+            Setting::Str(s) => f.write_str(s),
+        }
+    }
+}
+
+/// `T::command()` with a hidden negation for every boolean flag: `--no-<flag>`,
+/// or `--<flag>` for a flag that is itself named `no-<flag>`. Of a flag and
+/// its negation the last one given wins.
+pub fn command<T: Parser>() -> Command {
+    let cmd = T::command();
+    let negations: Vec<(clap::Id, String)> = cmd
+        .get_arguments()
+        .filter(|a| matches!(a.get_action(), ArgAction::SetTrue))
+        .filter_map(|a| {
+            let long = a.get_long()?;
+            let negation = match long.strip_prefix("no-") {
+                Some(positive) => positive.to_string(),
+                None => format!("no-{}", long),
+            };
+            let taken = cmd.get_arguments().any(|b| b.get_long() == Some(negation.as_str()));
+            (!taken).then(|| (a.get_id().clone(), negation))
+        })
+        .collect();
+
+    negations.into_iter().fold(cmd, |cmd, (id, negation)| {
+        cmd.mut_arg(id.clone(), |a| a.overrides_with(negation.clone()))
+            .arg(
+                Arg::new(negation.clone())
+                    .long(negation)
+                    .action(ArgAction::SetTrue)
+                    .overrides_with(id)
+                    .hide(true),
+            )
+    })
+}
+
+/// Parse `args` with [`command`], so flag negations are accepted.
+pub fn try_parse_from<T: Parser>(
+    args: impl IntoIterator<Item = impl Into<std::ffi::OsString> + Clone>,
+) -> Result<T, clap::Error> {
+    let matches = command::<T>().try_get_matches_from(args)?;
+    T::from_arg_matches(&matches)
+}
+
+/// Like [`try_parse_from`], but print the error and exit as clap does.
+pub fn parse_from<T: Parser>(
+    args: impl IntoIterator<Item = impl Into<std::ffi::OsString> + Clone>,
+) -> T {
+    try_parse_from(args).unwrap_or_else(|e| e.exit())
+}
+
+/// Settings and backend options of one level (file top level or one test).
+#[derive(Debug, Clone, Default)]
+struct Section {
+    settings: BTreeMap<String, Setting>,
+    backend: BackendOptions,
+}
+
+/// Parsed workload file.
+#[derive(Debug, Clone, Default)]
+pub struct Workload {
+    base: Section,
+    tests: Vec<Section>,
+}
+
+impl Workload {
+    /// Read and parse a workload file; the format follows the extension.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read config {}: {}", path, e))?;
+        let yaml = path.ends_with(".yaml") || path.ends_with(".yml");
+        Self::parse(&text, yaml).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Parse a workload from TOML, or YAML if `yaml` is set.
+    pub fn parse(text: &str, yaml: bool) -> Result<Self, String> {
+        let root: toml::Value = if yaml {
+            serde_norway::from_str(text).map_err(|e| e.to_string())?
+        } else {
+            toml::from_str(text).map_err(|e| e.to_string())?
+        };
+        let toml::Value::Table(mut table) = root else {
+            return Err("top level must be a table".into());
+        };
+
+        let tests = match table.remove("test") {
+            None => Vec::new(),
+            Some(toml::Value::Array(items)) => items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| match item {
+                    toml::Value::Table(t) => {
+                        parse_section(t).map_err(|e| format!("test {}: {}", i, e))
+                    }
+                    _ => Err(format!("test {}: must be a table", i)),
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err("'test' must be a list of tables".into()),
+        };
+
+        Ok(Self {
+            base: parse_section(table)?,
+            tests,
+        })
+    }
+
+    /// Parse the command line `args` (backend options already extracted into
+    /// `backend_options`) on top of this workload: one result per `test`
+    /// entry, or a single one if the file has none. Explicit flags and
+    /// backend options take precedence over the file.
+    pub fn parse_args<T: Parser>(
+        &self,
+        args: &[String],
+        backend_options: &BackendOptions,
+    ) -> Result<Vec<(T, BackendOptions)>, String> {
+        let tests: Vec<Option<usize>> = if self.tests.is_empty() {
+            vec![None]
+        } else {
+            (0..self.tests.len()).map(Some).collect()
+        };
+
+        let cmd = T::command();
+        tests
+            .into_iter()
+            .map(|test| {
+                let mut argv = args[..1].to_vec();
+                argv.extend(self.args(&cmd, test)?);
+                argv.extend_from_slice(&args[1..]);
+                let mut opts = self.backend_options(test);
+                opts.extend(backend_options.clone());
+                let parsed = try_parse_from::<T>(argv).map_err(|e| {
+                    // First line of clap's message, without its usage hint
+                    let text = e.to_string();
+                    let msg = text.lines().next().unwrap_or_default();
+                    let msg = msg.strip_prefix("error: ").unwrap_or(msg);
+                    match test {
+                        Some(i) => format!("test {}: {}", i, msg),
+                        None => msg.to_string(),
+                    }
+                })?;
+                Ok((parsed, opts))
+            })
+            .collect()
+    }
+
+    /// Number of `test` entries (0 if the file describes a single test).
+    pub fn num_tests(&self) -> usize {
+        self.tests.len()
+    }
+
+    /// Command-line arguments for `test` (or the top level only if None),
+    /// checked against the long options of `cmd`.
+    fn args(&self, cmd: &Command, test: Option<usize>) -> Result<Vec<String>, String> {
+        let mut settings = self.base.settings.clone();
+        if let Some(t) = test {
+            settings.extend(self.tests[t].settings.clone());
+        }
+
+        let mut args = Vec::new();
+        for (key, value) in &settings {
+            let arg = cmd
+                .get_arguments()
+                .find(|a| a.get_long() == Some(key.as_str()) && key != "config")
+                .ok_or_else(|| format!("unknown setting: {}", key))?;
+            match (arg.get_action(), value) {
+                (ArgAction::SetTrue, Setting::Bool(true)) => args.push(format!("--{}", key)),
+                (ArgAction::SetTrue, Setting::Bool(false)) => {}
+                (ArgAction::SetTrue, _) => {
+                    return Err(format!("{} expects true or false", key));
+                }
+                (ArgAction::Count, Setting::Int(n)) if *n >= 0 => {
+                    args.extend((0..*n).map(|_| format!("--{}", key)));
+                }
+                (ArgAction::Count, _) => {
+                    return Err(format!("{} expects a non-negative integer", key));
+                }
+                (_, Setting::Bool(_)) => return Err(format!("{} expects a value", key)),
+                (_, value) => args.push(format!("--{}={}", key, value)),
+            }
+        }
+        Ok(args)
+    }
+
+    /// Backend options for `test` (or the top level only if None).
+    fn backend_options(&self, test: Option<usize>) -> BackendOptions {
+        let mut opts = self.base.backend.clone();
+        if let Some(t) = test {
+            opts.extend(self.tests[t].backend.clone());
+        }
+        opts
+    }
+}
+
+/// Split a table into settings and the `backend` sub-table. Keys may use `_`
+/// in place of `-`.
+fn parse_section(table: toml::Table) -> Result<Section, String> {
+    let mut section = Section::default();
+    for (key, value) in table {
+        if key == "backend" {
+            let toml::Value::Table(backend) = value else {
+                return Err("'backend' must be a table".into());
+            };
+            flatten_backend("", backend, &mut section.backend)?;
+            continue;
+        }
+        let setting = scalar(&value).ok_or_else(|| format!("{} must be a scalar", key))?;
+        section.settings.insert(key.replace('_', "-"), setting);
+    }
+    Ok(section)
+}
+
+/// Flatten nested backend tables into `prefix.key` options. `true` becomes a
+/// flag and `false` leaves the option unset.
+fn flatten_backend(
+    prefix: &str,
+    table: toml::Table,
+    opts: &mut BackendOptions,
+) -> Result<(), String> {
+    for (key, value) in table {
+        let name = if prefix.is_empty() {
+            key
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            toml::Value::Table(t) => flatten_backend(&name, t, opts)?,
+            _ if !name.contains('.') => {
+                return Err(format!("backend option {} needs a prefix", name));
+            }
+            toml::Value::Boolean(true) => opts.insert(name, OptionValue::Flag),
+            toml::Value::Boolean(false) => {}
+            other => {
+                let setting = scalar(&other)
+                    .ok_or_else(|| format!("backend option {} must be a scalar", name))?;
+                opts.insert(name, OptionValue::Str(setting.to_string()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn scalar(value: &toml::Value) -> Option<Setting> {
+    match value {
+        toml::Value::Boolean(b) => Some(Setting::Bool(*b)),
+        toml::Value::Integer(n) => Some(Setting::Int(*n)),
+        toml::Value::Float(x) => Some(Setting::Float(*x)),
+        toml::Value::String(s) => Some(Setting::Str(s.clone())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Arg;
+
+    fn command() -> Command {
+        Command::new("bench")
+            .arg(Arg::new("block-size").long("block-size"))
+            .arg(Arg::new("transfer-size").long("transfer-size"))
+            .arg(Arg::new("queue-depth").long("queue-depth"))
+            .arg(Arg::new("write-file").long("write-file").action(ArgAction::SetTrue))
+            .arg(Arg::new("read-file").long("read-file").action(ArgAction::SetTrue))
+            .arg(Arg::new("verbose").long("verbose").action(ArgAction::Count))
+            .arg(Arg::new("config").long("config"))
+    }
+
+    #[test]
+    fn test_toml_tests_override_base() {
+        let text = r#"
+# shared settings
+block-size = "4m"
+write_file = true
+verbose = 2
+
+[backend.posix]
+engine = "uring"
+odirect = true
+
+[[test]]
+transfer-size = "1m"
+
+[[test]]
+transfer-size = "4m"
+write-file = false
+read-file = true
+backend.posix.engine = "threads"
+"#;
+        let w = Workload::parse(text, false).unwrap();
+        let cmd = command();
+        assert_eq!(w.num_tests(), 2);
+        assert_eq!(
+            w.args(&cmd, Some(0)).unwrap(),
+            [
+                "--block-size=4m",
+                "--transfer-size=1m",
+                "--verbose",
+                "--verbose",
+                "--write-file"
+            ]
+        );
+        assert_eq!(
+            w.args(&cmd, Some(1)).unwrap(),
+            ["--block-size=4m", "--read-file", "--transfer-size=4m", "--verbose", "--verbose"]
+        );
+
+        let opts = w.backend_options(Some(1));
+        assert_eq!(opts.get("posix.engine"), Some(&OptionValue::Str("threads".into())));
+        assert_eq!(opts.get("posix.odirect"), Some(&OptionValue::Flag));
+    }
+
+    #[test]
+    fn test_yaml() {
+        let text = "
+block-size: 1g
+queue-depth: 16
+backend:
+  posix.engine: uring
+";
+        let w = Workload::parse(text, true).unwrap();
+        assert_eq!(w.num_tests(), 0);
+        assert_eq!(
+            w.args(&command(), None).unwrap(),
+            ["--block-size=1g", "--queue-depth=16"]
+        );
+        assert_eq!(
+            w.backend_options(None).get("posix.engine"),
+            Some(&OptionValue::Str("uring".into()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let cmd = command();
+        let args = |text: &str| Workload::parse(text, false).and_then(|w| w.args(&cmd, None));
+        assert!(args("bogus = 1").is_err());
+        assert!(args("config = \"other.toml\"").is_err());
+        assert!(args("write-file = \"yes\"").is_err());
+        assert!(args("block-size = true").is_err());
+        assert!(args("block-size = [1, 2]").is_err());
+        assert!(args("test = 3").is_err());
+        assert!(Workload::parse("[backend]\nengine = \"uring\"", false).is_err());
+    }
+
+    #[derive(Parser, Debug)]
+    struct Flags {
+        #[arg(long)]
+        keep_file: bool,
+        #[arg(long)]
+        no_barriers: bool,
+        #[arg(long)]
+        block_size: Option<String>,
+    }
+
+    #[test]
+    fn test_command_line_turns_off_file_flags() {
+        let text = "keep-file = true\nno-barriers = true\nblock-size = \"4m\"";
+        let w = Workload::parse(text, false).unwrap();
+        let parse = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            let mut parsed = w.parse_args::<Flags>(&args, &BackendOptions::new()).unwrap();
+            parsed.remove(0).0
+        };
+
+        let flags = parse(&["bench"]);
+        assert!(flags.keep_file && flags.no_barriers);
+        assert_eq!(flags.block_size.as_deref(), Some("4m"));
+
+        // The negation given last wins over the file's flag
+        let flags = parse(&["bench", "--no-keep-file", "--barriers"]);
+        assert!(!flags.keep_file && !flags.no_barriers);
+        let flags = parse(&["bench", "--no-keep-file", "--keep-file"]);
+        assert!(flags.keep_file);
+
+        // Negations are hidden from --help
+        let cmd = super::command::<Flags>();
+        let negation = cmd.get_arguments().find(|a| a.get_long() == Some("no-keep-file"));
+        assert!(negation.unwrap().is_hide_set());
+    }
+}
@@ -5,8 +5,8 @@ use crate::params::MdtestParam;
 /// Rust mdtest benchmark — MPI-parallel metadata performance tester.
 ///
 /// Reference: `mdtest.c:2264-2318`
-#[derive(Parser, Debug)]
-#[command(name = "mdtest-bench", about = "Metadata benchmark (mdtest)")]
+#[derive(Parser, Debug, Clone)]
+#[command(name = "mdtest-bench", about = "Metadata benchmark (mdtest)", args_override_self = true)]
 pub struct CliArgs {
     /// I/O backend API
     #[arg(short = 'a', long = "api", default_value = "POSIX")]
@@ -132,6 +132,14 @@ pub struct CliArgs {
     #[arg(long = "rename-dirs")]
     pub rename_dirs: bool,
 
+    /// Abort the whole job on the first failed backend call
+    #[arg(long = "abort-on-error")]
+    pub abort_on_error: bool,
+
+    /// Workload file (TOML, or YAML by extension); explicit flags override it
+    #[arg(long = "config")]
+    pub config: Option<String>,
+
     /// Output results as JSON to stdout (suppresses text output)
     #[arg(long = "json")]
     pub json: bool,
@@ -139,6 +147,10 @@ pub struct CliArgs {
     /// Output results as JSON to file (text output still printed)
     #[arg(long = "json-file")]
     pub json_file: Option<String>,
+
+    /// Run N ranks as threads of this process instead of MPI processes
+    #[arg(long = "threads", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub threads: Option<usize>,
 }
 
 impl CliArgs {
@@ -170,6 +182,7 @@ impl CliArgs {
         p.verbose = self.verbose as i32;
         p.print_time = self.print_time;
         p.rename_dirs = self.rename_dirs;
+        p.abort_on_error = self.abort_on_error;
 
         // Default: if none of -C -T -E -r specified, enable all
         if !self.create_only && !self.stat_only && !self.read_only && !self.remove_only {
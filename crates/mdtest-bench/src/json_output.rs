@@ -52,6 +52,9 @@ pub struct MdtestJsonParameters {
 pub struct MdtestJsonIteration {
     pub iteration: i32,
     pub phases: Vec<MdtestJsonPhaseResult>,
+    /// First failed backend calls over all ranks
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub error_messages: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -60,6 +63,20 @@ pub struct MdtestJsonPhaseResult {
     pub rate: f64,
     pub time: f64,
     pub items: u64,
+    /// Failed backend calls, summed over all ranks
+    pub errors: u64,
+    pub latency: MdtestJsonLatency,
+}
+
+/// Per-item latency of a phase over all ranks, in seconds.
+#[derive(Serialize)]
+pub struct MdtestJsonLatency {
+    pub samples: u64,
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p99: f64,
+    pub max: f64,
 }
 
 #[derive(Serialize)]
@@ -111,17 +128,28 @@ pub fn build_mdtest_json(
             let mut phases = Vec::new();
             for phase_idx in 0..MDTEST_NUM_PHASES {
                 if result.time[phase_idx] > 0.0 || result.rate[phase_idx] > 0.0 {
+                    let lat = result.latency_summary(phase_idx);
                     phases.push(MdtestJsonPhaseResult {
                         phase: phase_name(phase_idx).to_string(),
                         rate: result.rate[phase_idx],
                         time: result.time[phase_idx],
                         items: result.items[phase_idx],
+                        errors: result.errors[phase_idx],
+                        latency: MdtestJsonLatency {
+                            samples: lat.samples,
+                            min: lat.min,
+                            mean: lat.mean,
+                            p50: lat.p50,
+                            p99: lat.p99,
+                            max: lat.max,
+                        },
                     });
                 }
             }
             MdtestJsonIteration {
                 iteration: i as i32,
                 phases,
+                error_messages: result.error_messages.clone(),
             }
         })
         .collect();
@@ -168,10 +196,6 @@ fn build_summary(params: &MdtestParam, all_results: &[MdtestResult]) -> Vec<Mdte
 
     // Item-level phases
     for phase in start..stop {
-        if phase == MdtestPhase::DirRead as usize {
-            continue;
-        }
-
         let values: Vec<f64> = if params.print_time {
             all_results.iter().map(|r| r.time[phase]).collect()
         } else {
@@ -309,10 +333,27 @@ mod tests {
         result.rate[MdtestPhase::FileCreate as usize] = 1000.0;
         result.time[MdtestPhase::FileCreate as usize] = 0.1;
         result.items[MdtestPhase::FileCreate as usize] = 100;
+        result.errors[MdtestPhase::FileCreate as usize] = 2;
+        result.error_messages = vec!["[rank 0] File creation: create /x: not found".into()];
+        for nanos in [1_000, 2_000, 9_000] {
+            result.latency[MdtestPhase::FileCreate as usize].record(nanos);
+        }
+        result.latency_sum[MdtestPhase::FileCreate as usize] = 12e-6;
 
         let doc = build_mdtest_json(&params, &[result], "mdtest-bench -n 100 -F");
         let json = serde_json::to_string_pretty(&doc).unwrap();
         assert!(json.contains("\"version\""));
         assert!(json.contains("\"File creation\""));
+        assert!(json.contains("\"errors\": 2"));
+        assert!(json.contains("create /x: not found"));
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let lat = &value["tests"][0]["iterations"][0]["phases"][0]["latency"];
+        assert_eq!(lat["samples"], 3);
+        assert_eq!(lat["min"], 1e-6);
+        // Percentiles are bucket edges, within 2% of the sample
+        assert!((lat["p50"].as_f64().unwrap() - 2e-6).abs() < 4e-8);
+        assert_eq!(lat["max"], 9e-6);
+        assert!((lat["mean"].as_f64().unwrap() - 4e-6).abs() < 1e-12);
     }
 }
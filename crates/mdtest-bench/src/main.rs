@@ -5,21 +5,67 @@ mod report;
 mod runner;
 mod tree;
 
-use clap::Parser;
-use mpi::topology::Color;
-use mpi::traits::*;
+use ior_core::{Comm, ThreadComm};
 
 use cli::CliArgs;
 
 fn main() {
-    let universe = mpi::initialize().unwrap();
-    let world = universe.world();
-    let rank = world.rank();
-    let mpi_size = world.size();
-
     let raw_args: Vec<String> = std::env::args().collect();
     let (filtered_args, backend_options) = ior_core::extract_backend_options(raw_args);
-    let args = CliArgs::parse_from(filtered_args);
+    let args: CliArgs = ior_core::workload::parse_from(&filtered_args);
+
+    // --threads: ranks are threads of this process instead of MPI processes
+    #[cfg(feature = "mpi")]
+    let threads = args.threads;
+    #[cfg(not(feature = "mpi"))]
+    let threads = Some(args.threads.unwrap_or(1));
+
+    match threads {
+        Some(n) => {
+            ThreadComm::run(n, |world| {
+                run(&world, args.clone(), backend_options.clone(), &filtered_args)
+            });
+        }
+        #[cfg(feature = "mpi")]
+        None => {
+            let universe = mpi::initialize().unwrap();
+            run(&universe.world(), args, backend_options, &filtered_args);
+            // MPI_Finalize happens on drop of `universe`
+        }
+        #[cfg(not(feature = "mpi"))]
+        None => unreachable!(),
+    }
+}
+
+/// Run the benchmark on the ranks of `world`.
+fn run<C: Comm>(
+    world: &C,
+    mut args: CliArgs,
+    mut backend_options: ior_core::BackendOptions,
+    filtered_args: &[String],
+) {
+    let rank = world.rank();
+    let world_size = world.size();
+
+    // Workload file (--config) with the command line applied on top
+    if let Some(path) = args.config.clone() {
+        let parsed = ior_core::Workload::load(&path).and_then(|w| {
+            if w.num_tests() > 0 {
+                return Err(format!("{}: mdtest-bench does not support 'test' entries", path));
+            }
+            w.parse_args::<CliArgs>(filtered_args, &backend_options)
+                .map_err(|e| format!("{}: {}", path, e))
+        });
+        match parsed {
+            Ok(mut invocations) => (args, backend_options) = invocations.remove(0),
+            Err(e) => {
+                if rank == 0 {
+                    eprintln!("ERROR: {}", e);
+                }
+                return;
+            }
+        }
+    }
 
     // Extract JSON flags before consuming args
     let json_stdout = args.json;
@@ -31,15 +77,28 @@ fn main() {
     let command_line = std::env::args().collect::<Vec<_>>().join(" ");
 
     let mut params = args.into_mdtest_param();
-    params.num_tasks = mpi_size;
+    params.num_tasks = world_size;
     params.compute_derived();
 
     // Task scaling defaults
     if params.first == 0 {
-        params.first = mpi_size;
+        params.first = world_size;
     }
     if params.last == 0 {
-        params.last = mpi_size;
+        params.last = world_size;
+    }
+
+    // Validate on rank 0 and broadcast, so every rank exits together
+    let mut valid = 1i32;
+    if rank == 0 {
+        for e in params.validate() {
+            eprintln!("ERROR: {}", e);
+            valid = 0;
+        }
+    }
+    world.broadcast_value(0, &mut valid);
+    if valid == 0 {
+        return;
     }
 
     // Print configuration (rank 0 only)
@@ -85,7 +144,7 @@ fn main() {
     }
 
     // Select backend and configure backend-specific options
-    let mut backend = select_backend(&params);
+    let mut backend = ior_core::with_faults(select_backend(&params), &backend_options);
     if let Err(e) = backend.as_mut().configure(&backend_options) {
         eprintln!("ERROR: invalid backend option: {}", e);
         world.barrier();
@@ -100,12 +159,7 @@ fn main() {
     let mut ntasks = params.first;
     while ntasks <= params.last {
         // Create subcommunicator with ntasks ranks
-        let color = if rank < ntasks {
-            Color::with_value(0)
-        } else {
-            Color::undefined()
-        };
-        let test_comm = world.split_by_color(color);
+        let test_comm = world.split((rank < ntasks).then_some(0), 0);
 
         if rank >= ntasks {
             ntasks += params.stride;
@@ -168,6 +222,7 @@ fn select_backend(params: &params::MdtestParam) -> Box<dyn ior_core::Aiori> {
         "POSIX" => Box::new(ior_backend_posix::PosixBackend::new(false)),
         "BENCHFS" => Box::new(ior_backend_benchfs::BenchfsBackend::new()),
         "CHFS" => Box::new(ior_backend_chfs::ChfsBackend::new()),
+        "MEMORY" => Box::new(ior_backend_memory::MemoryBackend::new()),
         other => {
             eprintln!("Unknown API: {}, falling back to POSIX", other);
             Box::new(ior_backend_posix::PosixBackend::new(false))
@@ -1,6 +1,9 @@
+use ior_core::ParamError;
+
 /// Mdtest benchmark parameters.
 ///
 /// Reference: `mdtest.c:101-190` (mdtest_options_t)
+#[derive(Clone)]
 pub struct MdtestParam {
     // Tree structure
     pub branch_factor: u32,
@@ -39,6 +42,9 @@ pub struct MdtestParam {
     pub stone_wall_timer_seconds: i32,
     pub barriers: bool,
 
+    // Errors
+    pub abort_on_error: bool,
+
     // Task scaling
     pub first: i32,
     pub last: i32,
@@ -51,6 +57,9 @@ pub struct MdtestParam {
     // Paths
     pub test_dir: String,
     pub api: String,
+    /// Prefix of tree directory names: `mdtest_tree`, or `mdtest_tree.<rank>`
+    /// for each rank with `-u`
+    pub base_tree_name: String,
 
     // MPI (computed)
     pub num_tasks: i32,
@@ -91,6 +100,8 @@ impl Default for MdtestParam {
             stone_wall_timer_seconds: 0,
             barriers: true,
 
+            abort_on_error: false,
+
             first: 0,
             last: 0,
             stride: 1,
@@ -100,6 +111,7 @@ impl Default for MdtestParam {
 
             test_dir: "./out".to_string(),
             api: "POSIX".to_string(),
+            base_tree_name: "mdtest_tree".to_string(),
 
             num_tasks: 0,
         }
@@ -124,6 +136,12 @@ impl MdtestParam {
                 ((bf.powi(self.depth + 1) - 1.0) / (bf - 1.0)) as u64;
         }
 
+        // A shared file run works on one file in the tree root
+        if self.shared_file {
+            self.items = 1;
+            self.items_per_dir = 0;
+        }
+
         // Compute items / items_per_dir relationship
         if self.items_per_dir > 0 {
             if self.items == 0 {
@@ -157,6 +175,72 @@ impl MdtestParam {
     }
 }
 
+impl MdtestParam {
+    /// Check the parameters (after `compute_derived` and the task-scaling
+    /// defaults) and return every problem found.
+    pub fn validate(&self) -> Vec<ParamError> {
+        let mut errors = Vec::new();
+        let mut at_least = |name, value: i64, min: i64, expected| {
+            if value < min {
+                errors.push(ParamError::OutOfRange {
+                    name,
+                    value,
+                    expected,
+                });
+            }
+        };
+        at_least("branch factor", self.branch_factor as i64, 1, "at least 1");
+        at_least("depth", self.depth as i64, 0, "non-negative");
+        at_least("iterations", self.iterations as i64, 1, "at least 1");
+        at_least("task stride", self.stride as i64, 1, "at least 1");
+        at_least("first task count", self.first as i64, 1, "at least 1");
+        at_least("neighbor stride", self.nstride as i64, 0, "non-negative");
+        at_least("stonewall timer", self.stone_wall_timer_seconds as i64, 0, "non-negative");
+
+        if self.last < self.first {
+            errors.push(ParamError::Conflict(format!(
+                "last task count ({}) is below first task count ({})",
+                self.last, self.first
+            )));
+        }
+        if self.last > self.num_tasks {
+            errors.push(ParamError::Conflict(format!(
+                "last task count ({}) exceeds the {} MPI tasks available",
+                self.last, self.num_tasks
+            )));
+        }
+        if self.shared_file && self.unique_dir_per_task {
+            errors.push(ParamError::Conflict(
+                "shared file (-S) cannot be used with unique directories (-u)".into(),
+            ));
+        }
+        if self.shared_file && self.collective_creates {
+            errors.push(ParamError::Conflict(
+                "shared file (-S) cannot be used with collective creates (-c)".into(),
+            ));
+        }
+        if self.shared_file && self.depth > 0 {
+            errors.push(ParamError::Conflict(
+                "shared file (-S) lives in the tree root and needs depth 0 (-z)".into(),
+            ));
+        }
+        if self.collective_creates && !self.barriers {
+            errors.push(ParamError::Conflict(
+                "collective creates (-c) need barriers between phases (no -B)".into(),
+            ));
+        }
+        if self.items_per_dir > 0 && !self.items.is_multiple_of(self.items_per_dir) {
+            errors.push(ParamError::NotMultiple {
+                name: "items",
+                value: self.items as i64,
+                of_name: "items per directory",
+                of: self.items_per_dir as i64,
+            });
+        }
+        errors
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,4 +278,44 @@ mod tests {
         // leaf dirs = 2^2 = 4
         assert_eq!(p.items, 40);
     }
+
+    #[test]
+    fn test_validate() {
+        let mut p = MdtestParam {
+            items: 100,
+            num_tasks: 4,
+            first: 4,
+            last: 4,
+            ..Default::default()
+        };
+        p.compute_derived();
+        assert_eq!(p.validate(), []);
+
+        p.items_per_dir = 30;
+        p.stride = 0;
+        p.last = 8;
+        let errors = p.validate();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].to_string(), "task stride must be at least 1 (got 0)");
+    }
+
+    #[test]
+    fn test_shared_file() {
+        let mut p = MdtestParam {
+            items: 100,
+            shared_file: true,
+            num_tasks: 2,
+            first: 2,
+            last: 2,
+            ..Default::default()
+        };
+        p.compute_derived();
+        assert_eq!((p.items, p.items_per_dir), (1, 1));
+        assert_eq!(p.validate(), []);
+
+        p.unique_dir_per_task = true;
+        p.collective_creates = true;
+        p.barriers = false;
+        assert_eq!(p.validate().len(), 3);
+    }
 }
@@ -1,5 +1,7 @@
+use ior_core::LatencyHistogram;
+
 use crate::params::MdtestParam;
-use crate::runner::{MdtestResult, MDTEST_NUM_PHASES, MdtestPhase, phase_name};
+use crate::runner::{LatencySummary, MdtestResult, MDTEST_NUM_PHASES, MdtestPhase, phase_name};
 
 /// Summarize and print mdtest results across iterations.
 ///
@@ -37,11 +39,6 @@ pub fn summarize_results(
 
     // Per-phase statistics for item-level operations
     for phase in start..stop {
-        // Skip DirRead (phase 2) - N/A like C mdtest
-        if phase == MdtestPhase::DirRead as usize {
-            continue;
-        }
-
         let mut iter_values: Vec<f64> = Vec::with_capacity(iterations);
 
         for result in all_results {
@@ -91,6 +88,72 @@ pub fn summarize_results(
     }
 
     println!();
+    print_latency(all_results);
+    print_errors(all_results);
+}
+
+/// Print per-item latency of every phase that timed items, over all
+/// iterations.
+fn print_latency(all_results: &[MdtestResult]) {
+    let summaries: Vec<(usize, LatencySummary)> = (0..MDTEST_NUM_PHASES)
+        .map(|phase| {
+            let mut hist = LatencyHistogram::new();
+            let mut sum = 0.0;
+            for result in all_results {
+                hist.merge(&result.latency[phase]);
+                sum += result.latency_sum[phase];
+            }
+            (phase, LatencySummary::from_histogram(&hist, sum))
+        })
+        .filter(|(_, summary)| summary.samples > 0)
+        .collect();
+    if summaries.is_empty() {
+        return;
+    }
+
+    println!("LATENCY (sec per item):");
+    println!(
+        "   {:<22} {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "Operation", "Min", "Mean", "p50", "p99", "Max"
+    );
+    println!(
+        "   {:<22} {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "---------", "---", "----", "---", "---", "---"
+    );
+    for (phase, lat) in summaries {
+        println!(
+            "   {:<22} {:>12.6} {:>12.6} {:>12.6} {:>12.6} {:>12.6}",
+            phase_name(phase),
+            lat.min,
+            lat.mean,
+            lat.p50,
+            lat.p99,
+            lat.max,
+        );
+    }
+    println!();
+}
+
+/// Print the failed backend calls of all iterations, if there were any.
+fn print_errors(all_results: &[MdtestResult]) {
+    let total: u64 = all_results.iter().flat_map(|r| r.errors).sum();
+    if total == 0 {
+        return;
+    }
+
+    println!("ERRORS: {} failed operations (rates above include them)", total);
+    for phase in 0..MDTEST_NUM_PHASES {
+        let count: u64 = all_results.iter().map(|r| r.errors[phase]).sum();
+        if count > 0 {
+            println!("   {:<22} {:>14}", phase_name(phase), count);
+        }
+    }
+    for (iter, result) in all_results.iter().enumerate() {
+        for msg in &result.error_messages {
+            println!("   iteration {}: {}", iter + 1, msg);
+        }
+    }
+    println!();
 }
 
 /// Print per-iteration verbose output.
@@ -104,10 +167,11 @@ pub fn print_iteration_result(result: &MdtestResult, iter_num: i32, verbose: i32
     for phase in 0..MDTEST_NUM_PHASES {
         if result.time[phase] > 0.0 || result.rate[phase] > 0.0 {
             println!(
-                "   {:<22}: {:>14.3} sec, {:>14.3} ops/sec",
+                "   {:<22}: {:>14.3} sec, {:>14.3} ops/sec, {} errors",
                 phase_name(phase),
                 result.time[phase],
                 result.rate[phase],
+                result.errors[phase],
             );
         }
     }
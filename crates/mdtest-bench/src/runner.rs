@@ -1,10 +1,12 @@
 use ior_core::timer::now;
-use ior_core::{Aiori, AlignedBuffer};
-use mpi::topology::SimpleCommunicator;
-use mpi::traits::*;
+use ior_core::histogram::HISTOGRAM_BUCKETS;
+use ior_core::{Aiori, AlignedBuffer, Comm, LatencyHistogram, ReduceOp};
 
 use crate::params::MdtestParam;
-use crate::tree;
+use crate::tree::{self, MAX_ERROR_MESSAGES, OpStats};
+
+/// Bytes of error messages each rank sends to rank 0 per iteration.
+const ERROR_MESSAGE_BYTES: usize = 2048;
 
 /// Number of mdtest benchmark phases.
 pub const MDTEST_NUM_PHASES: usize = 11;
@@ -58,6 +60,50 @@ pub struct MdtestResult {
     pub items: [u64; MDTEST_NUM_PHASES],
     pub stonewall_time: [f64; MDTEST_NUM_PHASES],
     pub stonewall_last_item: [u64; MDTEST_NUM_PHASES],
+    /// Failed backend calls, summed over all ranks
+    pub errors: [u64; MDTEST_NUM_PHASES],
+    /// First few failures, as `[rank N] <phase>: <op> <path>: <error>`
+    pub error_messages: Vec<String>,
+    /// Item latencies of each phase, merged over all ranks on rank 0
+    pub latency: Vec<LatencyHistogram>,
+    /// Sum of the latencies in `latency`, in seconds
+    pub latency_sum: [f64; MDTEST_NUM_PHASES],
+}
+
+impl MdtestResult {
+    /// Latency statistics of `phase`.
+    pub fn latency_summary(&self, phase: usize) -> LatencySummary {
+        LatencySummary::from_histogram(&self.latency[phase], self.latency_sum[phase])
+    }
+}
+
+/// Item latency statistics of one phase, in seconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LatencySummary {
+    pub samples: u64,
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl LatencySummary {
+    /// Summarize `hist`, whose latencies add up to `sum` seconds.
+    pub fn from_histogram(hist: &LatencyHistogram, sum: f64) -> Self {
+        let samples = hist.count();
+        if samples == 0 {
+            return Self::default();
+        }
+        Self {
+            samples,
+            min: hist.min() as f64 / 1e9,
+            mean: sum / samples as f64,
+            p50: hist.percentile_secs(50.0),
+            p99: hist.percentile_secs(99.0),
+            max: hist.percentile_secs(100.0),
+        }
+    }
 }
 
 impl Default for MdtestResult {
@@ -68,6 +114,10 @@ impl Default for MdtestResult {
             items: [0; MDTEST_NUM_PHASES],
             stonewall_time: [0.0; MDTEST_NUM_PHASES],
             stonewall_last_item: [0; MDTEST_NUM_PHASES],
+            errors: [0; MDTEST_NUM_PHASES],
+            error_messages: Vec::new(),
+            latency: vec![LatencyHistogram::new(); MDTEST_NUM_PHASES],
+            latency_sum: [0.0; MDTEST_NUM_PHASES],
         }
     }
 }
@@ -78,13 +128,25 @@ impl Default for MdtestResult {
 pub fn mdtest_iteration(
     params: &MdtestParam,
     backend: &dyn Aiori,
-    comm: &SimpleCommunicator,
+    comm: &impl Comm,
     result: &mut MdtestResult,
     _iter_num: i32,
 ) {
     let rank = comm.rank();
     let ntasks = comm.size();
-    let base_tree_name = format!("mdtest_tree.{}", rank);
+
+    // With -u every rank works in a tree of its own (ref: mdtest.c:2031-2035)
+    let params = &MdtestParam {
+        base_tree_name: tree::tree_name(params, rank),
+        ..params.clone()
+    };
+
+    // With --abort-on-error the first failure ends the whole job
+    let abort = |msg: &str| {
+        eprintln!("ERROR [{}] {}", rank, msg);
+        comm.abort(1)
+    };
+    let on_error: Option<&dyn Fn(&str)> = if params.abort_on_error { Some(&abort) } else { None };
 
     // Prepare test directory
     let test_dir = &params.test_dir;
@@ -100,29 +162,34 @@ pub fn mdtest_iteration(
 
         let start = now();
 
-        if params.unique_dir_per_task {
-            tree::create_remove_directory_tree(true, 0, test_dir, 0, params, backend);
-        } else if rank == 0 {
-            tree::create_remove_directory_tree(true, 0, test_dir, 0, params, backend);
-        }
+        let mut stats = OpStats::new(on_error);
+        let dirs = create_remove_trees(true, params, backend, comm, &mut stats);
+        record_phase(result, MdtestPhase::TreeCreate, stats);
 
         comm.barrier();
         let elapsed = now() - start;
 
-        result.rate[MdtestPhase::TreeCreate as usize] =
-            params.num_dirs_in_tree as f64 / elapsed;
+        result.rate[MdtestPhase::TreeCreate as usize] = dirs as f64 / elapsed;
         result.time[MdtestPhase::TreeCreate as usize] = elapsed;
-        result.items[MdtestPhase::TreeCreate as usize] = params.num_dirs_in_tree;
-        result.stonewall_last_item[MdtestPhase::TreeCreate as usize] = params.num_dirs_in_tree;
+        result.items[MdtestPhase::TreeCreate as usize] = dirs;
+        result.stonewall_last_item[MdtestPhase::TreeCreate as usize] = dirs;
     }
 
     // === SETUP NAMES ===
-    let mk_name = format!("mdtest.{}.", (rank + 0 * params.nstride).rem_euclid(ntasks));
-    let stat_name = format!("mdtest.{}.", (rank + 1 * params.nstride).rem_euclid(ntasks));
-    let read_name = format!("mdtest.{}.", (rank + 2 * params.nstride).rem_euclid(ntasks));
-    let rm_name = format!("mdtest.{}.", (rank + 3 * params.nstride).rem_euclid(ntasks));
+    // With -S every rank works on the same file
+    let item_name = |k: i32| {
+        if params.shared_file {
+            "mdtest.shared.".to_string()
+        } else {
+            format!("mdtest.{}.", (rank + k * params.nstride).rem_euclid(ntasks))
+        }
+    };
+    let mk_name = item_name(0);
+    let stat_name = item_name(1);
+    let read_name = item_name(2);
+    let rm_name = item_name(3);
 
-    let unique_mk_dir = format!("{}.0", base_tree_name);
+    let unique_mk_dir = format!("{}.0", params.base_tree_name);
 
     // Prepare page-aligned write buffer (required for O_DIRECT)
     let write_buf: Option<AlignedBuffer> = if params.write_bytes > 0 {
@@ -149,8 +216,8 @@ pub fn mdtest_iteration(
     if params.dirs_only && !params.shared_file {
         directory_test(
             params, backend, comm, result,
-            &unique_mk_dir, &mk_name, &stat_name, &rm_name,
-            rand_array.as_deref(),
+            &unique_mk_dir, &mk_name, &stat_name, &read_name, &rm_name,
+            rand_array.as_deref(), on_error,
         );
     }
 
@@ -160,7 +227,7 @@ pub fn mdtest_iteration(
             params, backend, comm, result,
             &unique_mk_dir, &mk_name, &stat_name, &read_name, &rm_name,
             write_buf.as_deref(), &mut read_buf,
-            rand_array.as_deref(),
+            rand_array.as_deref(), on_error,
         );
     }
 
@@ -169,44 +236,48 @@ pub fn mdtest_iteration(
     if params.remove_only {
         let start = now();
 
-        if params.unique_dir_per_task {
-            tree::create_remove_directory_tree(false, 0, test_dir, 0, params, backend);
-        } else if rank == 0 {
-            tree::create_remove_directory_tree(false, 0, test_dir, 0, params, backend);
-        }
+        let mut stats = OpStats::new(on_error);
+        let dirs = create_remove_trees(false, params, backend, comm, &mut stats);
+        record_phase(result, MdtestPhase::TreeRemove, stats);
 
         comm.barrier();
         let elapsed = now() - start;
 
-        result.rate[MdtestPhase::TreeRemove as usize] =
-            params.num_dirs_in_tree as f64 / elapsed;
+        result.rate[MdtestPhase::TreeRemove as usize] = dirs as f64 / elapsed;
         result.time[MdtestPhase::TreeRemove as usize] = elapsed;
-        result.items[MdtestPhase::TreeRemove as usize] = params.num_dirs_in_tree;
-        result.stonewall_last_item[MdtestPhase::TreeRemove as usize] = params.num_dirs_in_tree;
+        result.items[MdtestPhase::TreeRemove as usize] = dirs;
+        result.stonewall_last_item[MdtestPhase::TreeRemove as usize] = dirs;
 
         // Remove test directory
         if backend.access(test_dir, 0).unwrap_or(false) {
             let _ = backend.rmdir(test_dir);
         }
     }
+
+    reduce_errors(result, comm);
+    reduce_latency(result, comm);
 }
 
 /// Directory test: create/stat/read/rename/remove directories.
 ///
 /// Reference: `mdtest.c:937-1117` (directory_test)
+#[allow(clippy::too_many_arguments)]
 fn directory_test(
     params: &MdtestParam,
     backend: &dyn Aiori,
-    comm: &SimpleCommunicator,
+    comm: &impl Comm,
     result: &mut MdtestResult,
     path: &str,
     mk_name: &str,
     stat_name: &str,
+    read_name: &str,
     rm_name: &str,
     rand_array: Option<&[u64]>,
+    on_error: Option<&dyn Fn(&str)>,
 ) {
     let test_dir = &params.test_dir;
     let full_path = format!("{}/{}", test_dir, path);
+    let mut created = params.items;
 
     comm.barrier();
 
@@ -215,19 +286,26 @@ fn directory_test(
         phase_prepare(params, comm);
         let start = now();
 
-        let items_done = tree::create_remove_items(
-            0, true, true, &full_path, 0, params, backend, mk_name, rm_name, None,
-            start,
+        let mut stats = OpStats::new(on_error);
+        let items_done = create_remove_phase(
+            true, true, &full_path, params, backend, comm, mk_name, rm_name, None, start,
+            &mut stats,
         );
+        record_phase(result, MdtestPhase::DirCreate, stats);
 
         phase_end(params, comm);
         let elapsed = now() - start;
 
-        let effective_items = if params.stone_wall_timer_seconds > 0 { items_done } else { params.items };
+        let effective_items = if params.stone_wall_timer_seconds > 0 || params.collective_creates {
+            items_done
+        } else {
+            params.items
+        };
         result.rate[MdtestPhase::DirCreate as usize] = effective_items as f64 / elapsed;
         result.time[MdtestPhase::DirCreate as usize] = elapsed;
         result.items[MdtestPhase::DirCreate as usize] = effective_items;
         result.stonewall_last_item[MdtestPhase::DirCreate as usize] = items_done;
+        created = items_done;
     }
 
     // Stat phase
@@ -235,9 +313,13 @@ fn directory_test(
         phase_prepare(params, comm);
         let start = now();
 
+        let (stat_params, stat_path) = neighbor_tree(params, comm, 1);
+        let mut stats = OpStats::new(on_error);
         tree::mdtest_stat(
-            params.random_seed > 0, true, &full_path, params, backend, stat_name, rand_array,
+            params.random_seed > 0, true, &stat_path, &stat_params, backend, stat_name,
+            rand_array, &mut stats,
         );
+        record_phase(result, MdtestPhase::DirStat, stats);
 
         phase_end(params, comm);
         let elapsed = now() - start;
@@ -247,17 +329,40 @@ fn directory_test(
         result.items[MdtestPhase::DirStat as usize] = params.items;
     }
 
-    // Read phase (N/A for directories in C mdtest, but we record time)
+    // Read phase: list the tree holding read_name's directories
     if params.read_only {
+        let (read_params, read_path) = neighbor_tree(params, comm, 2);
+
         phase_prepare(params, comm);
         let start = now();
-        // Directory read is N/A in C mdtest
+
+        let mut stats = OpStats::new(on_error);
+        let listed =
+            tree::mdtest_readdir(&read_path, &read_params, backend, read_name, &mut stats);
+
         phase_end(params, comm);
         let elapsed = now() - start;
 
-        result.rate[MdtestPhase::DirRead as usize] = params.items as f64 / elapsed;
+        // Every directory created must be listed exactly once, by some rank;
+        // rank 0 records a mismatch as one error of the phase
+        let supported = comm.all_reduce_value(listed.is_ok() as i32, ReduceOp::Min) == 1;
+        let found = listed.unwrap_or(0);
+        let found_total = comm.all_reduce_value(found, ReduceOp::Sum);
+        let created_total = comm.all_reduce_value(created, ReduceOp::Sum);
+        if comm.rank() == 0 {
+            if !supported {
+                eprintln!("WARNING: {} backend cannot list directories", backend.name());
+            } else if found_total != created_total {
+                let mismatch =
+                    format!("listed {} of {} directories", found_total, created_total);
+                stats.record("readdir", &params.test_dir, mismatch);
+            }
+        }
+        record_phase(result, MdtestPhase::DirRead, stats);
+
+        result.rate[MdtestPhase::DirRead as usize] = found as f64 / elapsed;
         result.time[MdtestPhase::DirRead as usize] = elapsed;
-        result.items[MdtestPhase::DirRead as usize] = params.items;
+        result.items[MdtestPhase::DirRead as usize] = found;
     }
 
     // Rename phase
@@ -265,11 +370,18 @@ fn directory_test(
         phase_prepare(params, comm);
         let start = now();
 
-        tree::rename_dir_items(&full_path, params, backend, stat_name);
+        let (stat_params, stat_path) = neighbor_tree(params, comm, 1);
+        let mut stats = OpStats::new(on_error);
+        tree::rename_dir_items(&stat_path, &stat_params, backend, stat_name, false, &mut stats);
 
         phase_end(params, comm);
         let elapsed = now() - start;
 
+        // Untimed: put the names back for the remove phase
+        tree::rename_dir_items(&stat_path, &stat_params, backend, stat_name, true, &mut stats);
+        record_phase(result, MdtestPhase::DirRename, stats);
+        comm.barrier();
+
         result.rate[MdtestPhase::DirRename as usize] = params.items as f64 / elapsed;
         result.time[MdtestPhase::DirRename as usize] = elapsed;
         result.items[MdtestPhase::DirRename as usize] = params.items;
@@ -280,17 +392,21 @@ fn directory_test(
         phase_prepare(params, comm);
         let start = now();
 
-        tree::create_remove_items(
-            0, true, false, &full_path, 0, params, backend, mk_name, rm_name, None,
+        let (rm_params, rm_path) = neighbor_tree(params, comm, 3);
+        let mut stats = OpStats::new(on_error);
+        let removed = create_remove_phase(
+            true, false, &rm_path, &rm_params, backend, comm, mk_name, rm_name, None,
             0.0, // no stonewall for remove
+            &mut stats,
         );
+        record_phase(result, MdtestPhase::DirRemove, stats);
 
         phase_end(params, comm);
         let elapsed = now() - start;
 
-        result.rate[MdtestPhase::DirRemove as usize] = params.items as f64 / elapsed;
+        result.rate[MdtestPhase::DirRemove as usize] = removed as f64 / elapsed;
         result.time[MdtestPhase::DirRemove as usize] = elapsed;
-        result.items[MdtestPhase::DirRemove as usize] = params.items;
+        result.items[MdtestPhase::DirRemove as usize] = removed;
     }
 }
 
@@ -301,7 +417,7 @@ fn directory_test(
 fn file_test(
     params: &MdtestParam,
     backend: &dyn Aiori,
-    comm: &SimpleCommunicator,
+    comm: &impl Comm,
     result: &mut MdtestResult,
     path: &str,
     mk_name: &str,
@@ -311,6 +427,7 @@ fn file_test(
     write_buf: Option<&[u8]>,
     read_buf: &mut [u8],
     rand_array: Option<&[u64]>,
+    on_error: Option<&dyn Fn(&str)>,
 ) {
     let test_dir = &params.test_dir;
     let full_path = format!("{}/{}", test_dir, path);
@@ -322,15 +439,21 @@ fn file_test(
         phase_prepare(params, comm);
         let start = now();
 
-        let items_done = tree::create_remove_items(
-            0, false, true, &full_path, 0, params, backend, mk_name, rm_name, write_buf,
-            start,
+        let mut stats = OpStats::new(on_error);
+        let items_done = create_remove_phase(
+            false, true, &full_path, params, backend, comm, mk_name, rm_name, write_buf, start,
+            &mut stats,
         );
+        record_phase(result, MdtestPhase::FileCreate, stats);
 
         phase_end(params, comm);
         let elapsed = now() - start;
 
-        let effective_items = if params.stone_wall_timer_seconds > 0 { items_done } else { params.items };
+        let effective_items = if params.stone_wall_timer_seconds > 0 || params.collective_creates {
+            items_done
+        } else {
+            params.items
+        };
         result.rate[MdtestPhase::FileCreate as usize] = effective_items as f64 / elapsed;
         result.time[MdtestPhase::FileCreate as usize] = elapsed;
         result.items[MdtestPhase::FileCreate as usize] = effective_items;
@@ -342,9 +465,13 @@ fn file_test(
         phase_prepare(params, comm);
         let start = now();
 
+        let (stat_params, stat_path) = neighbor_tree(params, comm, 1);
+        let mut stats = OpStats::new(on_error);
         tree::mdtest_stat(
-            params.random_seed > 0, false, &full_path, params, backend, stat_name, rand_array,
+            params.random_seed > 0, false, &stat_path, &stat_params, backend, stat_name,
+            rand_array, &mut stats,
         );
+        record_phase(result, MdtestPhase::FileStat, stats);
 
         phase_end(params, comm);
         let elapsed = now() - start;
@@ -359,10 +486,13 @@ fn file_test(
         phase_prepare(params, comm);
         let start = now();
 
+        let (read_params, read_path) = neighbor_tree(params, comm, 2);
+        let mut stats = OpStats::new(on_error);
         tree::mdtest_read(
-            params.random_seed > 0, false, &full_path, params, backend, read_name,
-            rand_array, read_buf,
+            params.random_seed > 0, false, &read_path, &read_params, backend, read_name,
+            rand_array, read_buf, &mut stats,
         );
+        record_phase(result, MdtestPhase::FileRead, stats);
 
         phase_end(params, comm);
         let elapsed = now() - start;
@@ -377,30 +507,525 @@ fn file_test(
         phase_prepare(params, comm);
         let start = now();
 
-        tree::create_remove_items(
-            0, false, false, &full_path, 0, params, backend, mk_name, rm_name, None,
+        let (rm_params, rm_path) = neighbor_tree(params, comm, 3);
+        let mut stats = OpStats::new(on_error);
+        let removed = create_remove_phase(
+            false, false, &rm_path, &rm_params, backend, comm, mk_name, rm_name, None,
             0.0, // no stonewall for remove
+            &mut stats,
         );
+        record_phase(result, MdtestPhase::FileRemove, stats);
 
         phase_end(params, comm);
         let elapsed = now() - start;
 
-        result.rate[MdtestPhase::FileRemove as usize] = params.items as f64 / elapsed;
+        result.rate[MdtestPhase::FileRemove as usize] = removed as f64 / elapsed;
         result.time[MdtestPhase::FileRemove as usize] = elapsed;
-        result.items[MdtestPhase::FileRemove as usize] = params.items;
+        result.items[MdtestPhase::FileRemove as usize] = removed;
+    }
+}
+
+/// Parameters and tree root of the rank `k` neighbor strides (-N) away,
+/// whose items the stat (k = 1), read (2) and remove (3) phases work on.
+fn neighbor_tree(params: &MdtestParam, comm: &impl Comm, k: i32) -> (MdtestParam, String) {
+    let neighbor = (comm.rank() + k * params.nstride).rem_euclid(comm.size());
+    let neighbor_params = MdtestParam {
+        base_tree_name: tree::tree_name(params, neighbor),
+        ..params.clone()
+    };
+    let path = format!("{}/{}.0", params.test_dir, neighbor_params.base_tree_name);
+    (neighbor_params, path)
+}
+
+/// Create or remove the directory trees. The shared tree (no -u, no -c) is
+/// split across ranks level by level, with a barrier between levels so that
+/// parents exist before their children are created and are removed only
+/// after them; other trees are handled whole by the ranks of `tree_owners`.
+/// Returns the number of directories counted for this rank: the whole
+/// shared tree, or every directory of the trees it handled.
+fn create_remove_trees(
+    create: bool,
+    params: &MdtestParam,
+    backend: &dyn Aiori,
+    comm: &impl Comm,
+    stats: &mut OpStats,
+) -> u64 {
+    let (rank, ntasks) = (comm.rank(), comm.size());
+    if !params.unique_dir_per_task && !params.collective_creates {
+        let levels = 0..=params.depth;
+        let levels: Vec<i32> = if create { levels.collect() } else { levels.rev().collect() };
+        for level in levels {
+            tree::create_remove_tree_level(
+                create, level, rank, ntasks, &params.test_dir, params, backend, stats,
+            );
+            comm.barrier();
+        }
+        return params.num_dirs_in_tree;
+    }
+
+    let owners = tree_owners(params, rank, ntasks);
+    let dirs = owners.len() as u64 * params.num_dirs_in_tree;
+    for owner in owners {
+        let tree_params = MdtestParam {
+            base_tree_name: tree::tree_name(params, owner),
+            ..params.clone()
+        };
+        tree::create_remove_directory_tree(
+            create, 0, &params.test_dir, 0, &tree_params, backend, stats,
+        );
+    }
+    dirs
+}
+
+/// Ranks whose trees `rank` creates and removes: its own with -u, every
+/// rank's on rank 0 with -u -c, and the one shared tree on rank 0 with -c.
+fn tree_owners(params: &MdtestParam, rank: i32, ntasks: i32) -> std::ops::Range<i32> {
+    match (params.unique_dir_per_task, params.collective_creates, rank) {
+        (true, false, _) => rank..rank + 1,
+        (true, true, 0) => 0..ntasks,
+        (false, _, 0) => 0..1,
+        _ => 0..0,
+    }
+}
+
+/// Create or remove this rank's items; with -c rank 0 does it for every
+/// rank and the others do nothing. Returns the number of items handled.
+#[allow(clippy::too_many_arguments)]
+fn create_remove_phase(
+    dirs: bool,
+    create: bool,
+    path: &str,
+    params: &MdtestParam,
+    backend: &dyn Aiori,
+    comm: &impl Comm,
+    mk_name: &str,
+    rm_name: &str,
+    write_buf: Option<&[u8]>,
+    stonewall_start: f64,
+    stats: &mut OpStats,
+) -> u64 {
+    if !params.collective_creates {
+        return tree::create_remove_items(
+            0, dirs, create, path, 0, params, backend, mk_name, rm_name, write_buf,
+            stonewall_start, stats,
+        );
+    }
+    if comm.rank() != 0 {
+        return 0;
+    }
+    tree::collective_create_remove(
+        dirs, create, comm.size(), params, backend, write_buf, stonewall_start, stats,
+    )
+}
+
+/// Keep this rank's failures and latencies of `phase` until the end of the
+/// iteration.
+fn record_phase(result: &mut MdtestResult, phase: MdtestPhase, stats: OpStats) {
+    result.errors[phase as usize] += stats.errors;
+    for msg in stats.messages {
+        result.error_messages.push(format!("{}: {}", phase_name(phase as usize), msg));
+    }
+    result.latency[phase as usize].merge(&stats.latency);
+    result.latency_sum[phase as usize] += stats.latency_sum;
+}
+
+/// Sum the error counts over all ranks and collect the first messages of
+/// every rank on rank 0 (collective). Other ranks keep only their own.
+fn reduce_errors(result: &mut MdtestResult, comm: &impl Comm) {
+    let local = result.errors;
+    comm.all_reduce(&local, &mut result.errors, ReduceOp::Sum);
+    if result.errors.iter().all(|&count| count == 0) {
+        return;
+    }
+
+    let mut packed = result
+        .error_messages
+        .iter()
+        .take(MAX_ERROR_MESSAGES)
+        .map(|msg| format!("[rank {}] {}", comm.rank(), msg))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .into_bytes();
+    packed.resize(ERROR_MESSAGE_BYTES, 0);
+
+    let root_len = if comm.rank() == 0 { ERROR_MESSAGE_BYTES * comm.size() as usize } else { 0 };
+    let mut all = vec![0u8; root_len];
+    comm.gather(0, &packed, &mut all);
+    if comm.rank() == 0 {
+        result.error_messages = all
+            .chunks(ERROR_MESSAGE_BYTES)
+            .flat_map(|chunk| {
+                let len = chunk.iter().position(|&b| b == 0).unwrap_or(chunk.len());
+                String::from_utf8_lossy(&chunk[..len])
+                    .lines()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .take(MAX_ERROR_MESSAGES)
+            .collect();
+    }
+}
+
+/// Merge the latency histograms of all ranks on rank 0 (collective). Other
+/// ranks keep their own.
+fn reduce_latency(result: &mut MdtestResult, comm: &impl Comm) {
+    let local_sum = result.latency_sum;
+    comm.reduce(0, &local_sum, &mut result.latency_sum, ReduceOp::Sum);
+    for hist in &mut result.latency {
+        let mut counts = vec![0u64; HISTOGRAM_BUCKETS];
+        comm.reduce(0, hist.counts(), &mut counts, ReduceOp::Sum);
+        let min = comm.reduce_value(0, hist.min(), ReduceOp::Min);
+        let max = comm.reduce_value(0, hist.max(), ReduceOp::Max);
+        if comm.rank() == 0 {
+            *hist = LatencyHistogram::from_parts(counts, min, max);
+        }
     }
 }
 
 /// Prepare for a phase: optional barrier.
-fn phase_prepare(params: &MdtestParam, comm: &SimpleCommunicator) {
+fn phase_prepare(params: &MdtestParam, comm: &impl Comm) {
     if params.barriers {
         comm.barrier();
     }
 }
 
 /// End a phase: optional barrier.
-fn phase_end(params: &MdtestParam, comm: &SimpleCommunicator) {
+fn phase_end(params: &MdtestParam, comm: &impl Comm) {
     if params.barriers {
         comm.barrier();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ior_backend_memory::{MemoryBackend, Namespace};
+
+    use super::*;
+
+    #[test]
+    fn test_threaded_iteration() {
+        for unique_dir_per_task in [false, true] {
+            let mut params = MdtestParam {
+                depth: 1,
+                branch_factor: 2,
+                items: 6,
+                create_only: true,
+                stat_only: true,
+                read_only: true,
+                remove_only: true,
+                dirs_only: true,
+                files_only: true,
+                write_bytes: 16,
+                read_bytes: 16,
+                nstride: 1,
+                unique_dir_per_task,
+                test_dir: "/mdtest".to_string(),
+                num_tasks: 3,
+                ..Default::default()
+            };
+            params.compute_derived();
+
+            let namespace = Arc::new(Namespace::default());
+            let results = ior_core::ThreadComm::run(3, |comm| {
+                let backend = MemoryBackend::with_namespace(Arc::clone(&namespace));
+                let mut result = MdtestResult::default();
+                mdtest_iteration(&params, &backend, &comm, &mut result, 0);
+                result
+            });
+
+            // Item counts are per rank; the directory read lists another rank's items
+            for phase in [
+                MdtestPhase::DirCreate,
+                MdtestPhase::DirRead,
+                MdtestPhase::FileCreate,
+                MdtestPhase::FileRead,
+            ] {
+                for result in &results {
+                    assert_eq!(result.items[phase as usize], 6, "{}", phase_name(phase as usize));
+                }
+            }
+            assert_eq!(results[0].items[MdtestPhase::TreeCreate as usize], 3);
+            assert_eq!(results[0].errors, [0; MDTEST_NUM_PHASES]);
+            assert_eq!(namespace.paths(), Vec::<String>::new());
+
+            // Every item of every rank is timed; rank 0 holds the merged histograms
+            for phase in [MdtestPhase::DirCreate, MdtestPhase::FileStat, MdtestPhase::FileRemove] {
+                let lat = results[0].latency_summary(phase as usize);
+                assert_eq!(lat.samples, 18);
+                assert!(lat.min <= lat.p50 && lat.p50 <= lat.p99 && lat.p99 <= lat.max);
+                assert!(lat.min <= lat.mean && lat.mean <= lat.max);
+                assert_eq!(results[1].latency[phase as usize].count(), 6);
+            }
+            // Directory read times one listing per tree directory
+            assert_eq!(results[0].latency_summary(MdtestPhase::DirRead as usize).samples, 9);
+            assert_eq!(results[0].latency_summary(MdtestPhase::TreeCreate as usize).samples, 0);
+        }
+    }
+
+    #[test]
+    fn test_errors_are_counted_over_ranks() {
+        // Stat files that were never created
+        let mut params = MdtestParam {
+            items: 4,
+            stat_only: true,
+            files_only: true,
+            test_dir: "/mdtest".to_string(),
+            num_tasks: 2,
+            ..Default::default()
+        };
+        params.compute_derived();
+
+        let namespace = Arc::new(Namespace::default());
+        let results = ior_core::ThreadComm::run(2, |comm| {
+            let backend = MemoryBackend::with_namespace(Arc::clone(&namespace));
+            let mut result = MdtestResult::default();
+            mdtest_iteration(&params, &backend, &comm, &mut result, 0);
+            result
+        });
+
+        for result in &results {
+            assert_eq!(result.errors[MdtestPhase::FileStat as usize], 8);
+            assert_eq!(result.errors.iter().sum::<u64>(), 8);
+        }
+        let messages = &results[0].error_messages;
+        assert_eq!(messages.len(), MAX_ERROR_MESSAGES);
+        assert_eq!(
+            messages[0],
+            "[rank 0] File stat: stat /mdtest/mdtest_tree.0/file.mdtest.0.0: not found"
+        );
+        assert!(messages[4].starts_with("[rank 1] File stat: stat "), "{}", messages[4]);
+    }
+
+    #[test]
+    fn test_removing_missing_files_is_an_error() {
+        let mut params = MdtestParam {
+            items: 3,
+            remove_only: true,
+            files_only: true,
+            test_dir: "/mdtest".to_string(),
+            num_tasks: 2,
+            ..Default::default()
+        };
+        params.compute_derived();
+
+        let namespace = Arc::new(Namespace::default());
+        let results = ior_core::ThreadComm::run(2, |comm| {
+            let backend = MemoryBackend::with_namespace(Arc::clone(&namespace));
+            let mut result = MdtestResult::default();
+            mdtest_iteration(&params, &backend, &comm, &mut result, 0);
+            result
+        });
+
+        assert_eq!(results[0].errors[MdtestPhase::FileRemove as usize], 6);
+        assert_eq!(
+            results[0].error_messages[0],
+            "[rank 0] File removal: delete /mdtest/mdtest_tree.0/file.mdtest.0.0: not found"
+        );
+    }
+
+    #[test]
+    fn test_directory_read_mismatch_is_an_error() {
+        let mut params = MdtestParam {
+            items: 3,
+            create_only: true,
+            read_only: true,
+            dirs_only: true,
+            test_dir: "/mdtest".to_string(),
+            num_tasks: 2,
+            ..Default::default()
+        };
+        params.compute_derived();
+
+        // A stray directory matching the item names is listed too
+        let namespace = Arc::new(Namespace::default());
+        let backend = MemoryBackend::with_namespace(Arc::clone(&namespace));
+        for dir in ["/mdtest", "/mdtest/mdtest_tree.0", "/mdtest/mdtest_tree.0/dir.mdtest.1.9"] {
+            backend.mkdir(dir, 0o755).unwrap();
+        }
+        let results = ior_core::ThreadComm::run(2, |comm| {
+            let backend = MemoryBackend::with_namespace(Arc::clone(&namespace));
+            let mut result = MdtestResult::default();
+            mdtest_iteration(&params, &backend, &comm, &mut result, 0);
+            result
+        });
+
+        for result in &results {
+            assert_eq!(result.errors[MdtestPhase::DirRead as usize], 1);
+        }
+        let expected = "[rank 0] Directory read: readdir /mdtest: listed 7 of 6 directories";
+        let messages = &results[0].error_messages;
+        assert!(messages.iter().any(|m| m == expected), "{:?}", messages);
+    }
+
+    #[test]
+    fn test_collective_creates() {
+        for unique_dir_per_task in [false, true] {
+            let mut params = MdtestParam {
+                depth: 1,
+                branch_factor: 2,
+                items: 6,
+                create_only: true,
+                stat_only: true,
+                read_only: true,
+                dirs_only: true,
+                files_only: true,
+                collective_creates: true,
+                unique_dir_per_task,
+                test_dir: "/mdtest".to_string(),
+                num_tasks: 3,
+                ..Default::default()
+            };
+            params.compute_derived();
+
+            let namespace = Arc::new(Namespace::default());
+            let results = ior_core::ThreadComm::run(3, |comm| {
+                let backend = MemoryBackend::with_namespace(Arc::clone(&namespace));
+                let mut result = MdtestResult::default();
+                mdtest_iteration(&params, &backend, &comm, &mut result, 0);
+                result
+            });
+
+            // Rank 0 created every rank's items, where each rank reads them back
+            for phase in [MdtestPhase::DirCreate, MdtestPhase::FileCreate] {
+                assert_eq!(results[0].items[phase as usize], 18);
+                assert_eq!(results[1].items[phase as usize], 0);
+            }
+            // Likewise for the trees: one per rank with -u, else the shared one
+            let trees = if unique_dir_per_task { 3 } else { 1 };
+            assert_eq!(results[0].items[MdtestPhase::TreeCreate as usize], trees * 3);
+            assert_eq!(results[1].items[MdtestPhase::TreeCreate as usize], 0);
+            for result in &results {
+                assert_eq!(result.items[MdtestPhase::DirRead as usize], 6);
+                assert_eq!(result.errors, [0; MDTEST_NUM_PHASES]);
+            }
+            let paths = namespace.paths();
+            for rank in 0..3 {
+                let tree = if unique_dir_per_task {
+                    format!("/mdtest/mdtest_tree.{rank}.0")
+                } else {
+                    "/mdtest/mdtest_tree.0".to_string()
+                };
+                assert!(paths.contains(&format!("{tree}/file.mdtest.{rank}.0")), "{paths:?}");
+                assert!(paths.contains(&format!("{tree}/dir.mdtest.{rank}.0")), "{paths:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_file() {
+        let mut params = MdtestParam {
+            items: 10,
+            create_only: true,
+            stat_only: true,
+            read_only: true,
+            dirs_only: true,
+            files_only: true,
+            shared_file: true,
+            write_bytes: 8,
+            read_bytes: 8,
+            test_dir: "/mdtest".to_string(),
+            num_tasks: 3,
+            ..Default::default()
+        };
+        params.compute_derived();
+
+        let namespace = Arc::new(Namespace::default());
+        let results = ior_core::ThreadComm::run(3, |comm| {
+            let backend = MemoryBackend::with_namespace(Arc::clone(&namespace));
+            let mut result = MdtestResult::default();
+            mdtest_iteration(&params, &backend, &comm, &mut result, 0);
+            result
+        });
+
+        // No directory test, and a single file for all ranks
+        assert_eq!(
+            namespace.paths(),
+            ["/mdtest", "/mdtest/mdtest_tree.0", "/mdtest/mdtest_tree.0/file.mdtest.shared.0"]
+        );
+        for result in &results {
+            assert_eq!(result.items[MdtestPhase::DirCreate as usize], 0);
+            assert_eq!(result.items[MdtestPhase::FileCreate as usize], 1);
+            assert_eq!(result.items[MdtestPhase::FileStat as usize], 1);
+        }
+    }
+
+    #[test]
+    fn test_parallel_tree_matches_serial_layout() {
+        let mut params = MdtestParam {
+            depth: 3,
+            branch_factor: 3,
+            create_only: true,
+            test_dir: "/mdtest".to_string(),
+            num_tasks: 4,
+            ..Default::default()
+        };
+        params.compute_derived();
+
+        // Reference: the whole tree from one process
+        let serial_namespace = Arc::new(Namespace::default());
+        let serial = MemoryBackend::with_namespace(Arc::clone(&serial_namespace));
+        serial.mkdir("/mdtest", 0o755).unwrap();
+        let mut stats = OpStats::new(None);
+        tree::create_remove_directory_tree(true, 0, "/mdtest", 0, &params, &serial, &mut stats);
+        assert_eq!(stats.errors, 0);
+
+        let namespace = Arc::new(Namespace::default());
+        let results = ior_core::ThreadComm::run(4, |comm| {
+            let backend = MemoryBackend::with_namespace(Arc::clone(&namespace));
+            let mut result = MdtestResult::default();
+            mdtest_iteration(&params, &backend, &comm, &mut result, 0);
+            result
+        });
+        assert_eq!(results[0].items[MdtestPhase::TreeCreate as usize], 40);
+        assert_eq!(results[0].errors, [0; MDTEST_NUM_PHASES]);
+        assert_eq!(namespace.paths(), serial_namespace.paths());
+
+        // Removal runs leaves first, so no rmdir finds a non-empty directory
+        let params = MdtestParam { create_only: false, remove_only: true, ..params };
+        let results = ior_core::ThreadComm::run(4, |comm| {
+            let backend = MemoryBackend::with_namespace(Arc::clone(&namespace));
+            let mut result = MdtestResult::default();
+            mdtest_iteration(&params, &backend, &comm, &mut result, 0);
+            result
+        });
+        assert_eq!(results[0].errors, [0; MDTEST_NUM_PHASES]);
+        assert_eq!(namespace.paths(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_items_are_created_inside_the_tree() {
+        let mut params = MdtestParam {
+            depth: 2,
+            branch_factor: 2,
+            items: 14,
+            create_only: true,
+            dirs_only: true,
+            test_dir: "/mdtest".to_string(),
+            num_tasks: 1,
+            ..Default::default()
+        };
+        params.compute_derived();
+
+        let backend = MemoryBackend::with_namespace(Arc::default());
+        ior_core::ThreadComm::run(1, |comm| {
+            let mut result = MdtestResult::default();
+            mdtest_iteration(&params, &backend, &comm, &mut result, 0);
+        });
+
+        // Two directories in each of the 7 tree directories, none beside it
+        let tree = "/mdtest/mdtest_tree.0";
+        for dir in ["", "/mdtest_tree.1", "/mdtest_tree.1/mdtest_tree.4"] {
+            let mut handle = backend.opendir(&format!("{}{}", tree, dir)).unwrap();
+            let mut items = 0;
+            while let Some(name) = backend.readdir(&mut handle).unwrap() {
+                items += name.starts_with("dir.mdtest.0.") as u32;
+            }
+            assert_eq!(items, 2, "{}", dir);
+        }
+        let mut root = backend.opendir("/mdtest").unwrap();
+        assert_eq!(backend.readdir(&mut root).unwrap().as_deref(), Some("mdtest_tree.0"));
+        assert_eq!(backend.readdir(&mut root).unwrap(), None);
+    }
+}
@@ -1,10 +1,68 @@
+use std::fmt;
+
 use ior_core::handle::{OpenFlags, XferDir};
-use ior_core::{now, Aiori};
+use ior_core::{now, Aiori, IorError, LatencyHistogram};
 
 use crate::params::MdtestParam;
 
-/// Base tree name prefix used for directory hierarchy.
-const BASE_TREE_NAME: &str = "mdtest_tree";
+/// Error messages kept per rank and phase; later failures are only counted.
+pub const MAX_ERROR_MESSAGES: usize = 5;
+
+/// Failed backend calls and item latencies of one phase on this rank.
+pub struct OpStats<'a> {
+    pub errors: u64,
+    pub messages: Vec<String>,
+    /// Latency of each item operation (e.g. a file create with its write
+    /// and close), failed ones included
+    pub latency: LatencyHistogram,
+    /// Sum of the recorded latencies in seconds, for the mean
+    pub latency_sum: f64,
+    on_error: Option<&'a dyn Fn(&str)>,
+}
+
+impl<'a> OpStats<'a> {
+    /// `on_error` sees every failure as it happens, e.g. to abort the job.
+    pub fn new(on_error: Option<&'a dyn Fn(&str)>) -> Self {
+        Self {
+            errors: 0,
+            messages: Vec::new(),
+            latency: LatencyHistogram::new(),
+            latency_sum: 0.0,
+            on_error,
+        }
+    }
+
+    /// Record the latency of an item operation that began at `start`.
+    pub fn sample(&mut self, start: f64) {
+        let secs = now() - start;
+        self.latency.record_secs(secs);
+        self.latency_sum += secs;
+    }
+
+    /// Record a failed `op` on `path`: a backend error, or a description of
+    /// a wrong result.
+    pub fn record(&mut self, op: &str, path: &str, err: impl fmt::Display) {
+        let msg = format!("{} {}: {}", op, path, err);
+        if let Some(on_error) = self.on_error {
+            on_error(&msg);
+        }
+        self.errors += 1;
+        if self.messages.len() < MAX_ERROR_MESSAGES {
+            self.messages.push(msg);
+        }
+    }
+
+    /// Pass the value of a successful call through and record a failed one.
+    pub fn check<T>(&mut self, op: &str, path: &str, result: Result<T, IorError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.record(op, path, err);
+                None
+            }
+        }
+    }
+}
 
 /// Create or remove the hierarchical directory tree.
 ///
@@ -16,12 +74,13 @@ pub fn create_remove_directory_tree(
     dir_num: u64,
     params: &MdtestParam,
     backend: &dyn Aiori,
+    stats: &mut OpStats,
 ) {
     if curr_depth == 0 {
-        let dir = format!("{}/{}.{}/", base_path, BASE_TREE_NAME, dir_num);
+        let dir = format!("{}/{}.{}/", base_path, params.base_tree_name, dir_num);
 
         if create {
-            let _ = backend.mkdir(&dir, 0o755);
+            stats.check("mkdir", &dir, backend.mkdir(&dir, 0o755));
         }
 
         create_remove_directory_tree(
@@ -31,20 +90,21 @@ pub fn create_remove_directory_tree(
             dir_num + 1,
             params,
             backend,
+            stats,
         );
 
         if !create {
-            let _ = backend.rmdir(&dir);
+            stats.check("rmdir", &dir, backend.rmdir(&dir));
         }
     } else if curr_depth <= params.depth {
         let mut curr_dir = dir_num;
 
         for _ in 0..params.branch_factor {
-            let dir_suffix = format!("{}.{}/", BASE_TREE_NAME, curr_dir);
+            let dir_suffix = format!("{}.{}/", params.base_tree_name, curr_dir);
             let temp_path = format!("{}{}", base_path, dir_suffix);
 
             if create {
-                let _ = backend.mkdir(&temp_path, 0o755);
+                stats.check("mkdir", &temp_path, backend.mkdir(&temp_path, 0o755));
             }
 
             create_remove_directory_tree(
@@ -54,10 +114,11 @@ pub fn create_remove_directory_tree(
                 curr_dir * params.branch_factor as u64 + 1,
                 params,
                 backend,
+                stats,
             );
 
             if !create {
-                let _ = backend.rmdir(&temp_path);
+                stats.check("rmdir", &temp_path, backend.rmdir(&temp_path));
             }
 
             curr_dir += 1;
@@ -65,6 +126,52 @@ pub fn create_remove_directory_tree(
     }
 }
 
+/// Create or remove this rank's share of tree level `level`: every
+/// `ntasks`-th directory of the level, starting at the rank's own index.
+/// The caller orders the levels and synchronizes ranks between them.
+#[allow(clippy::too_many_arguments)]
+pub fn create_remove_tree_level(
+    create: bool,
+    level: i32,
+    rank: i32,
+    ntasks: i32,
+    base_path: &str,
+    params: &MdtestParam,
+    backend: &dyn Aiori,
+    stats: &mut OpStats,
+) {
+    // Directories are numbered breadth first, so a level is a contiguous range
+    let bf = params.branch_factor as u64;
+    let mut first = 0;
+    let mut width = 1;
+    for _ in 0..level {
+        first += width;
+        width *= bf;
+    }
+
+    for index in (rank as u64..width).step_by(ntasks as usize) {
+        let dir = tree_dir_path(base_path, first + index, params);
+        if create {
+            stats.check("mkdir", &dir, backend.mkdir(&dir, 0o755));
+        } else {
+            stats.check("rmdir", &dir, backend.rmdir(&dir));
+        }
+    }
+}
+
+/// Path of tree directory `dir_num`, in the numbering of
+/// `create_remove_directory_tree`.
+fn tree_dir_path(base_path: &str, dir_num: u64, params: &MdtestParam) -> String {
+    let bf = params.branch_factor as u64;
+    let mut path = String::new();
+    let mut dir = dir_num;
+    while dir > 0 {
+        path = format!("/{}.{}{}", params.base_tree_name, dir, path);
+        dir = (dir - 1) / bf;
+    }
+    format!("{}/{}.0{}", base_path, params.base_tree_name, path)
+}
+
 /// Build item path for a given item number.
 ///
 /// Given an item number and items_per_dir, constructs the full path by
@@ -94,12 +201,12 @@ pub fn build_item_path(
     let mut dir = parent_dir;
 
     // Prepend parent directory
-    path = format!("{}.{}/{}", BASE_TREE_NAME, dir, path);
+    path = format!("{}.{}/{}", params.base_tree_name, dir, path);
 
     // Walk up to tree root
     while dir > params.branch_factor as u64 {
         dir = (dir - 1) / params.branch_factor as u64;
-        path = format!("{}.{}/{}", BASE_TREE_NAME, dir, path);
+        path = format!("{}.{}/{}", params.base_tree_name, dir, path);
     }
 
     format!("{}/{}", base_path, path)
@@ -123,6 +230,7 @@ pub fn create_remove_items(
     rm_name: &str,
     write_buf: Option<&[u8]>,
     stonewall_start: f64,
+    stats: &mut OpStats,
 ) -> u64 {
     let mut count: u64 = 0;
 
@@ -131,7 +239,7 @@ pub fn create_remove_items(
         if !params.leaf_only || (params.depth == 0 && params.leaf_only) {
             count += create_remove_items_helper(
                 dirs, create, path, 0, params, backend, mk_name, rm_name, write_buf,
-                stonewall_start,
+                stonewall_start, stats,
             );
         }
 
@@ -148,13 +256,14 @@ pub fn create_remove_items(
                 rm_name,
                 write_buf,
                 stonewall_start,
+                stats,
             );
         }
     } else if curr_depth <= params.depth {
         let mut curr_dir = dir_num;
 
         for _ in 0..params.branch_factor {
-            let temp_path = format!("{}/{}.{}/", path, BASE_TREE_NAME, curr_dir);
+            let temp_path = format!("{}/{}.{}", path, params.base_tree_name, curr_dir);
 
             // Create items in this branch
             if !params.leaf_only || (params.leaf_only && curr_depth == params.depth) {
@@ -169,6 +278,7 @@ pub fn create_remove_items(
                     rm_name,
                     write_buf,
                     stonewall_start,
+                    stats,
                 );
             }
 
@@ -185,6 +295,7 @@ pub fn create_remove_items(
                 rm_name,
                 write_buf,
                 stonewall_start,
+                stats,
             );
 
             curr_dir += 1;
@@ -194,6 +305,48 @@ pub fn create_remove_items(
     count
 }
 
+/// Create or remove the items of every rank from this one (`-c`).
+///
+/// Each rank's items go into that rank's tree under its own name prefix,
+/// exactly where the rank itself would have put them.
+///
+/// Reference: `mdtest.c` (collective_create_remove)
+#[allow(clippy::too_many_arguments)]
+pub fn collective_create_remove(
+    dirs: bool,
+    create: bool,
+    ntasks: i32,
+    params: &MdtestParam,
+    backend: &dyn Aiori,
+    write_buf: Option<&[u8]>,
+    stonewall_start: f64,
+    stats: &mut OpStats,
+) -> u64 {
+    let mut count = 0;
+    for rank in 0..ntasks {
+        let rank_params = MdtestParam {
+            base_tree_name: tree_name(params, rank),
+            ..params.clone()
+        };
+        let path = format!("{}/{}.0", params.test_dir, rank_params.base_tree_name);
+        let name = format!("mdtest.{}.", rank);
+        count += create_remove_items(
+            0, dirs, create, &path, 0, &rank_params, backend, &name, &name, write_buf,
+            stonewall_start, stats,
+        );
+    }
+    count
+}
+
+/// Name prefix of the tree directories `rank` works in.
+pub fn tree_name(params: &MdtestParam, rank: i32) -> String {
+    if params.unique_dir_per_task {
+        format!("mdtest_tree.{}", rank)
+    } else {
+        "mdtest_tree".to_string()
+    }
+}
+
 /// Helper: create or remove items at a single directory level.
 ///
 /// Returns the number of items processed. May be less than `items_per_dir`
@@ -211,6 +364,7 @@ fn create_remove_items_helper(
     rm_name: &str,
     write_buf: Option<&[u8]>,
     stonewall_start: f64,
+    stats: &mut OpStats,
 ) -> u64 {
     let name = if create { mk_name } else { rm_name };
 
@@ -222,21 +376,29 @@ fn create_remove_items_helper(
             return i;
         }
 
+        let start = now();
         if dirs {
-            let item_path = format!("{}dir.{}{}", path, name, item_num + i);
+            let item_path = format!("{}/dir.{}{}", path, name, item_num + i);
             if create {
-                let _ = backend.mkdir(&item_path, 0o755);
+                stats.check("mkdir", &item_path, backend.mkdir(&item_path, 0o755));
             } else {
-                let _ = backend.rmdir(&item_path);
+                stats.check("rmdir", &item_path, backend.rmdir(&item_path));
             }
         } else {
-            let item_path = format!("{}file.{}{}", path, name, item_num + i);
+            let item_path = format!("{}/file.{}{}", path, name, item_num + i);
             if create {
-                create_file(&item_path, params, backend, write_buf);
+                create_file(&item_path, params, backend, write_buf, stats);
             } else {
-                let _ = backend.delete(&item_path);
+                match backend.delete_strict(&item_path) {
+                    // Only one rank removes a shared file (-S)
+                    Err(IorError::NotFound) if params.shared_file => {}
+                    result => {
+                        stats.check("delete", &item_path, result);
+                    }
+                }
             }
         }
+        stats.sample(start);
     }
 
     params.items_per_dir
@@ -250,39 +412,70 @@ fn create_file(
     params: &MdtestParam,
     backend: &dyn Aiori,
     write_buf: Option<&[u8]>,
+    stats: &mut OpStats,
 ) {
     if params.make_node && params.write_bytes == 0 {
-        let _ = backend.mknod(path);
+        match backend.mknod(path) {
+            // Only one rank creates a shared file (-S)
+            Err(IorError::Io(libc::EEXIST)) if params.shared_file => {}
+            result => {
+                stats.check("mknod", path, result);
+            }
+        }
         return;
     }
 
-    let handle = match backend.create(path, OpenFlags::WRONLY | OpenFlags::CREAT) {
-        Ok(h) => h,
-        Err(_) => return,
+    let Some(handle) = stats.check(
+        "create",
+        path,
+        backend.create(path, OpenFlags::WRONLY | OpenFlags::CREAT),
+    ) else {
+        return;
     };
 
     if let Some(buf) = write_buf {
         if params.write_bytes > 0 {
-            let _ = backend.xfer_sync(
+            let len = params.write_bytes as i64;
+            let written = backend.xfer_sync(
                 &handle,
                 XferDir::Write,
                 buf.as_ptr() as *mut u8,
-                params.write_bytes as i64,
+                len,
                 0,
             );
+            check_transfer(stats, "write", path, written, len);
         }
     }
 
     if params.sync_file {
-        let _ = backend.fsync(&handle);
+        stats.check("fsync", path, backend.fsync(&handle));
     }
 
-    let _ = backend.close(handle);
+    stats.check("close", path, backend.close(handle));
+}
+
+/// Record a failed or short transfer of `len` bytes.
+fn check_transfer(
+    stats: &mut OpStats,
+    op: &str,
+    path: &str,
+    result: Result<i64, IorError>,
+    len: i64,
+) {
+    match result {
+        Ok(done) if done != len => {
+            stats.record(op, path, IorError::ShortTransfer { done, len });
+        }
+        result => {
+            stats.check(op, path, result);
+        }
+    }
 }
 
 /// Stat items in the tree, supporting random access order.
 ///
 /// Reference: `mdtest.c:569-648` (mdtest_stat)
+#[allow(clippy::too_many_arguments)]
 pub fn mdtest_stat(
     random: bool,
     dirs: bool,
@@ -291,6 +484,7 @@ pub fn mdtest_stat(
     backend: &dyn Aiori,
     stat_name: &str,
     rand_array: Option<&[u64]>,
+    stats: &mut OpStats,
 ) {
     let stop_items = if params.directory_loops != 1 {
         params.items_per_dir
@@ -318,7 +512,9 @@ pub fn mdtest_stat(
         let item_name = format!("{}.{}{}", prefix, stat_name, adjusted_num);
         let full_path = build_item_path(path, prefix, &item_name, adjusted_num, params);
 
-        let _ = backend.stat(&full_path);
+        let start = now();
+        stats.check("stat", &full_path, backend.stat(&full_path));
+        stats.sample(start);
     }
 }
 
@@ -334,6 +530,7 @@ pub fn mdtest_read(
     read_name: &str,
     rand_array: Option<&[u64]>,
     read_buf: &mut [u8],
+    stats: &mut OpStats,
 ) {
     if dirs || params.read_bytes == 0 {
         return; // No reading for directories or zero-byte reads
@@ -363,24 +560,110 @@ pub fn mdtest_read(
         let item_name = format!("file.{}{}", read_name, adjusted_num);
         let full_path = build_item_path(path, "file", &item_name, adjusted_num, params);
 
-        let handle = match backend.open(&full_path, OpenFlags::RDONLY) {
-            Ok(h) => h,
-            Err(_) => continue,
-        };
+        let start = now();
+        read_file(&full_path, params, backend, read_buf, stats);
+        stats.sample(start);
+    }
+}
 
-        let _ = backend.xfer_sync(
-            &handle,
-            XferDir::Read,
-            read_buf.as_mut_ptr(),
-            params.read_bytes as i64,
-            0,
-        );
+/// Open a file, read its first `read_bytes` and close it.
+fn read_file(
+    path: &str,
+    params: &MdtestParam,
+    backend: &dyn Aiori,
+    read_buf: &mut [u8],
+    stats: &mut OpStats,
+) {
+    let Some(handle) = stats.check("open", path, backend.open(path, OpenFlags::RDONLY)) else {
+        return;
+    };
+
+    let len = params.read_bytes as i64;
+    let read = backend.xfer_sync(&handle, XferDir::Read, read_buf.as_mut_ptr(), len, 0);
+    check_transfer(stats, "read", path, read, len);
+
+    stats.check("close", path, backend.close(handle));
+}
+
+/// Directories of the tree that hold items, in the order
+/// `create_remove_items` visits them.
+fn item_dirs(curr_depth: i32, path: &str, dir_num: u64, params: &MdtestParam, out: &mut Vec<String>) {
+    if curr_depth == 0 {
+        if !params.leaf_only || params.depth == 0 {
+            out.push(path.to_string());
+        }
+        if params.depth > 0 {
+            item_dirs(curr_depth + 1, path, dir_num + 1, params, out);
+        }
+    } else if curr_depth <= params.depth {
+        for curr_dir in dir_num..dir_num + params.branch_factor as u64 {
+            let temp_path = format!("{}/{}.{}", path, params.base_tree_name, curr_dir);
+            if !params.leaf_only || curr_depth == params.depth {
+                out.push(temp_path.clone());
+            }
+            item_dirs(
+                curr_depth + 1,
+                &temp_path,
+                curr_dir * params.branch_factor as u64 + 1,
+                params,
+                out,
+            );
+        }
+    }
+}
+
+/// List every directory of the tree that holds items and count the
+/// directories named after `read_name`.
+///
+/// Directories that cannot be listed count nothing. Fails only if the
+/// backend has no directory listing at all.
+pub fn mdtest_readdir(
+    path: &str,
+    params: &MdtestParam,
+    backend: &dyn Aiori,
+    read_name: &str,
+    stats: &mut OpStats,
+) -> Result<u64, IorError> {
+    let prefix = format!("dir.{}", read_name);
+    let mut dirs = Vec::new();
+    item_dirs(0, path, 0, params, &mut dirs);
+
+    let mut found = 0;
+    for dir_path in &dirs {
+        let start = now();
+        found += list_dir(dir_path, &prefix, backend, stats)?;
+        stats.sample(start);
+    }
+    Ok(found)
+}
 
-        let _ = backend.close(handle);
+/// List one directory and count the entries starting with `prefix`.
+fn list_dir(
+    dir_path: &str,
+    prefix: &str,
+    backend: &dyn Aiori,
+    stats: &mut OpStats,
+) -> Result<u64, IorError> {
+    let mut dir = match backend.opendir(dir_path) {
+        Ok(dir) => dir,
+        Err(IorError::NotSupported) => return Err(IorError::NotSupported),
+        Err(err) => {
+            stats.record("opendir", dir_path, err);
+            return Ok(0);
+        }
+    };
+    let mut found = 0;
+    while let Some(Some(name)) = stats.check("readdir", dir_path, backend.readdir(&mut dir)) {
+        if name.starts_with(prefix) {
+            found += 1;
+        }
     }
+    stats.check("closedir", dir_path, backend.closedir(dir));
+    Ok(found)
 }
 
-/// Rename directories in the tree.
+/// Rename directories in the tree, or with `restore` rename them back so
+/// the remove phase finds them.
 ///
 /// Reference: `mdtest.c:1046-1068`
 pub fn rename_dir_items(
@@ -388,6 +671,8 @@ pub fn rename_dir_items(
     params: &MdtestParam,
     backend: &dyn Aiori,
     stat_name: &str,
+    restore: bool,
+    stats: &mut OpStats,
 ) {
     let stop_items = if params.directory_loops != 1 {
         params.items_per_dir
@@ -406,10 +691,17 @@ pub fn rename_dir_items(
 
         let old_name = format!("dir.{}{}", stat_name, adjusted_num);
         let new_name = format!("dir.{}{}.renamed", stat_name, adjusted_num);
-        let old_path = build_item_path(path, "dir", &old_name, adjusted_num, params);
-        let new_path = build_item_path(path, "dir", &new_name, adjusted_num, params);
+        let mut old_path = build_item_path(path, "dir", &old_name, adjusted_num, params);
+        let mut new_path = build_item_path(path, "dir", &new_name, adjusted_num, params);
+        if restore {
+            std::mem::swap(&mut old_path, &mut new_path);
+        }
 
-        let _ = backend.rename(&old_path, &new_path);
+        let start = now();
+        stats.check("rename", &old_path, backend.rename(&old_path, &new_path));
+        if !restore {
+            stats.sample(start);
+        }
     }
 }
 